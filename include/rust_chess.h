@@ -0,0 +1,117 @@
+#ifndef RUST_CHESS_H
+#define RUST_CHESS_H
+
+/* Generated by cbindgen from src/ffi.rs, don't edit by hand */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// Whether the game goes on or how it has ended, see `chess_board_status`
+typedef enum ChessStatus {
+  CHESS_STATUS_ONGOING,
+  CHESS_STATUS_WHITE_WINS,
+  CHESS_STATUS_BLACK_WINS,
+  CHESS_STATUS_DRAW,
+} ChessStatus;
+
+// A position with the movements leading to it, handled through pointers
+typedef struct ChessBoard ChessBoard;
+
+// A movement between two fields, numbered from 0 (a1) to 63 (h8). `promotion` and
+// `drop` are uppercase piece letters like `'Q'`, or 0 if the movement doesn't promote
+// or drop a piece. For a drop from the hand `from` equals `to`.
+typedef struct ChessMove {
+  uint8_t from;
+  uint8_t to;
+  char promotion;
+  char drop;
+} ChessMove;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a board with the starting position of standard chess.
+struct ChessBoard *chess_board_new(void);
+
+// Creates a board with the position `fen`, played by the rules of `variant`, like
+// `"atomic"`, or of standard chess if it is null. Returns null if the FEN or the
+// variant is invalid.
+//
+// # Safety
+//
+// `fen` has to be a null-terminated string, `variant` one as well or null.
+struct ChessBoard *chess_board_from_fen(const char *fen, const char *variant);
+
+// Releases `board`. Null is ignored.
+//
+// # Safety
+//
+// `board` has to be returned by `chess_board_new` or `chess_board_from_fen` and
+// can't be used afterwards.
+void chess_board_free(struct ChessBoard *board);
+
+// Writes up to `capacity` legal movements of the side to move to `moves` and returns
+// how many there are, which may be more than `capacity`. Returns 0 for a null board.
+//
+// # Safety
+//
+// `board` has to be a valid board or null and `moves` has to point to space for
+// `capacity` movements, nothing is written if it is null.
+size_t chess_board_legal_moves(const struct ChessBoard *board,
+                               struct ChessMove *moves,
+                               size_t capacity);
+
+// Performs `movement` if it is legal, returning whether it was performed.
+//
+// # Safety
+//
+// `board` has to be a valid board or null.
+bool chess_board_make_move(struct ChessBoard *board, struct ChessMove movement);
+
+// Takes back the last movement, returning false if there was none.
+//
+// # Safety
+//
+// `board` has to be a valid board or null.
+bool chess_board_unmake_move(struct ChessBoard *board);
+
+// Writes the FEN of the position as a null-terminated string to `buffer` if it fits
+// into `capacity` bytes, and returns its length without the terminating null, like
+// `snprintf`. Returns 0 for a null board.
+//
+// # Safety
+//
+// `board` has to be a valid board or null and `buffer` has to point to `capacity`
+// bytes, nothing is written if it is null.
+size_t chess_board_fen(const struct ChessBoard *board, char *buffer, size_t capacity);
+
+// Returns the side to move, 0 for White and 1 for Black, also for a null board.
+//
+// # Safety
+//
+// `board` has to be a valid board or null.
+uint8_t chess_board_side_to_move(const struct ChessBoard *board);
+
+// Returns whether the game goes on or who has won, `Ongoing` for a null board.
+//
+// # Safety
+//
+// `board` has to be a valid board or null.
+enum ChessStatus chess_board_status(const struct ChessBoard *board);
+
+// Searches the best movement for at most `millis` milliseconds and writes it to `best`.
+// Returns false if the game is over or a pointer is null.
+//
+// # Safety
+//
+// `board` has to be a valid board or null and `best` has to point to a movement or
+// be null.
+bool chess_board_search(const struct ChessBoard *board, uint32_t millis, struct ChessMove *best);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RUST_CHESS_H */
@@ -1,16 +1,160 @@
-use std::usize;
+use std::fmt;
 
+use super::movement::Move;
 use super::piece;
 use super::piece::Piece;
 use super::position::Pos;
+use super::variant::{self, Variant};
+
+mod attacks;
+use attacks::attacked;
+pub use attacks::Control;
+mod fen;
+pub use fen::START_FEN;
+mod san;
+mod zobrist;
+
+/// Index of the king side castling in `Board::castling`
+const KING_SIDE: usize = 0;
+/// Index of the queen side castling in `Board::castling`
+const QUEEN_SIDE: usize = 1;
+
+/// Types of the pieces which can be held in hand, in variants like Crazyhouse
+pub const IN_HAND: [piece::Type; 5] = [
+    piece::Type::Pawn(false),
+    piece::Type::Knight,
+    piece::Type::Bishop,
+    piece::Type::Rook,
+    piece::Type::Queen,
+];
 
 /// Wraps a chess board implemented as an array of size 64.
 /// Each field is an option:
 /// - Some means the field has a piece
 /// - None means the field is empty
+#[derive(Clone)]
 pub struct Board {
     fields: Box<[Option<Piece>; 64]>,
     current_move: piece::Side,
+    /// Columns of the rooks which can still castle, indexed by side and then by
+    /// `KING_SIDE` or `QUEEN_SIDE`
+    castling: [[Option<char>; 2]; 2],
+    /// Field passed by a pawn which has just moved two rows, where it can be captured
+    en_passant: Option<Pos>,
+    /// Number of half-moves since the last capture or pawn movement
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    /// Zobrist key of the current position, updated with every change of the board
+    key: u64,
+    /// Castling is written as the king capturing its own rook, as in Chess960
+    chess960: bool,
+    /// Rules of the game
+    variant: &'static dyn Variant,
+    /// Number of checks given by White and by Black, if the variant counts them
+    checks: [u8; 2],
+    /// Number of pieces in the hand of each side, indexed by side and then like `IN_HAND`
+    hand: [[u8; 5]; 2],
+    /// Fields of the pieces which were promoted from pawns, one bit per field,
+    /// if the variant has drops
+    promoted: u64,
+    history: Vec<Undo>,
+}
+
+/// Everything needed to take back a movement
+#[derive(Clone)]
+struct Undo {
+    movement: Move,
+    /// The moved piece as it was before the movement
+    moved: Piece,
+    /// The captured piece and its position, which differs from `movement.end` en passant
+    captured: Option<(Pos, Piece)>,
+    /// Pieces around `movement.end` removed by the explosion of a capture in Atomic chess
+    exploded: Vec<(Pos, Piece)>,
+    castling: [[Option<char>; 2]; 2],
+    en_passant: Option<Pos>,
+    halfmove_clock: u32,
+    checks: [u8; 2],
+    hand: [[u8; 5]; 2],
+    promoted: u64,
+    key: u64,
+}
+
+/// State of the game on a board
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Status {
+    /// The side to move has legal moves and no draw rule applies
+    Ongoing,
+    /// The side to move is checkmated, the contained side has won
+    Checkmate(piece::Side),
+    /// The side to move has no legal moves but isn't in check
+    Stalemate,
+    /// 50 moves by each side were made without a capture or a pawn movement
+    FiftyMoveRule,
+    /// The same position occurred three times
+    Repetition,
+    /// Neither side has enough pieces to checkmate
+    InsufficientMaterial,
+    /// The contained side has won by a rule of the variant, described by the text
+    VariantWin(piece::Side, &'static str),
+}
+
+impl Status {
+    /// Returns true if the game has ended
+    pub fn is_over(&self) -> bool {
+        *self != Status::Ongoing
+    }
+
+    /// Returns a short name of the status for other programs: `ongoing`, `checkmate`,
+    /// `stalemate`, `fifty-move-rule`, `repetition`, `insufficient-material` or
+    /// `variant-win`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Ongoing => "ongoing",
+            Status::Checkmate(_) => "checkmate",
+            Status::Stalemate => "stalemate",
+            Status::FiftyMoveRule => "fifty-move-rule",
+            Status::Repetition => "repetition",
+            Status::InsufficientMaterial => "insufficient-material",
+            Status::VariantWin(..) => "variant-win",
+        }
+    }
+
+    /// Returns the result as written in PGN: `1-0`, `0-1`, `1/2-1/2` or `*` for an
+    /// ongoing game.
+    pub fn result(&self) -> &'static str {
+        match self {
+            Status::Ongoing => "*",
+            Status::Checkmate(piece::Side::White) | Status::VariantWin(piece::Side::White, _) => {
+                "1-0"
+            }
+            Status::Checkmate(piece::Side::Black) | Status::VariantWin(piece::Side::Black, _) => {
+                "0-1"
+            }
+            _ => "1/2-1/2",
+        }
+    }
+
+    /// Returns the side which has won, if any
+    pub fn winner(&self) -> Option<piece::Side> {
+        match self {
+            Status::Checkmate(side) | Status::VariantWin(side, _) => Some(*side),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Ongoing => write!(f, "the game is ongoing"),
+            Status::Checkmate(winner) => write!(f, "checkmate, {winner} wins"),
+            Status::Stalemate => write!(f, "stalemate, draw"),
+            Status::FiftyMoveRule => write!(f, "draw by the fifty-move rule"),
+            Status::Repetition => write!(f, "draw by threefold repetition"),
+            Status::InsufficientMaterial => write!(f, "draw by insufficient material"),
+            Status::VariantWin(winner, reason) => write!(f, "{reason}, {winner} wins"),
+        }
+    }
 }
 
 impl Board {
@@ -59,10 +203,163 @@ impl Board {
         fill_figures(1);
         fill_figures(8);
 
+        let mut board = Self::empty(fields, piece::Side::White);
+        board.castling = [[Some('h'), Some('a')]; 2];
+        board.key = board.compute_key();
+        board
+    }
+
+    /// Constructs a board with the given pieces, no castling rights and no history.
+    /// The Zobrist key has to be computed by the caller once the state is complete.
+    fn empty(fields: Box<[Option<Piece>; 64]>, current_move: piece::Side) -> Self {
         Self {
             fields,
-            current_move: piece::Side::White,
+            current_move,
+            castling: [[None; 2]; 2],
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            key: 0,
+            chess960: false,
+            variant: &variant::Standard,
+            checks: [0; 2],
+            hand: [[0; 5]; 2],
+            promoted: 0,
+            history: Vec::new(),
+        }
+    }
+
+    /// Constructs a board with one of the 960 starting positions of Chess960 (Fischer Random
+    /// Chess), identified by its Scharnagl number `index` from 0 to 959. Number 518 is the
+    /// standard starting position. The king castles by moving onto its own rook, for example
+    /// `e1h1`, and ends on the same fields as in standard chess.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    ///
+    /// let b = Board::new_chess960(0).unwrap();
+    /// assert_eq!(b.to_fen(), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1");
+    ///
+    /// assert!(Board::new_chess960(960).is_err());
+    /// ```
+    pub fn new_chess960(index: u16) -> Result<Board, &'static str> {
+        if index >= 960 {
+            return Err("Chess960 positions are numbered from 0 to 959.");
+        }
+        // Fields of the first row which are still empty
+        let mut free: Vec<char> = ('a'..='h').collect();
+        let mut row: [Option<piece::Type>; 8] = [None; 8];
+        let mut place = |column: char, t: piece::Type, free: &mut Vec<char>| {
+            row[(column as u8 - b'a') as usize] = Some(t);
+            free.retain(|&c| c != column);
+        };
+
+        let mut n = index as usize;
+        // Bishops on fields of different colours: b, d, f, h and a, c, e, g
+        place(
+            (b'b' + 2 * (n % 4) as u8).into(),
+            piece::Type::Bishop,
+            &mut free,
+        );
+        n /= 4;
+        place(
+            (b'a' + 2 * (n % 4) as u8).into(),
+            piece::Type::Bishop,
+            &mut free,
+        );
+        n /= 4;
+        place(free[n % 6], piece::Type::Queen, &mut free);
+        n /= 6;
+        // The knights take two of the five remaining fields, in the order of this table
+        const KNIGHTS: [(usize, usize); 10] = [
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (1, 2),
+            (1, 3),
+            (1, 4),
+            (2, 3),
+            (2, 4),
+            (3, 4),
+        ];
+        let (first, second) = KNIGHTS[n];
+        let knights = [free[first], free[second]];
+        for column in knights {
+            place(column, piece::Type::Knight, &mut free);
         }
+        // The king stands between the rooks
+        place(free[0], piece::Type::Rook, &mut free);
+        place(free[0], piece::Type::King, &mut free);
+        place(free[0], piece::Type::Rook, &mut free);
+
+        let mut fields = Box::new([None; 64]);
+        for (column, t) in ('a'..='h').zip(row) {
+            let t = t.expect("every field of the first row gets a piece");
+            for (side, first, pawns) in [(piece::Side::White, 1, 2), (piece::Side::Black, 8, 7)] {
+                fields[Pos::at(column, first)] = Some(Piece::new(t, side));
+                fields[Pos::at(column, pawns)] = Some(Piece::new(piece::Type::Pawn(false), side));
+            }
+        }
+
+        let mut board = Self::empty(fields, piece::Side::White);
+        let rooks: Vec<char> = ('a'..='h')
+            .filter(|&c| row[(c as u8 - b'a') as usize] == Some(piece::Type::Rook))
+            .collect();
+        board.castling = [[Some(rooks[1]), Some(rooks[0])]; 2];
+        board.chess960 = true;
+        board.key = board.compute_key();
+        Ok(board)
+    }
+
+    /// Constructs a board with the starting position of `variant`, whose rules apply
+    /// to every movement and to the end of the game.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    /// use rust_chess::core::variant::Horde;
+    ///
+    /// let b = Board::new_variant(&Horde);
+    /// assert_eq!(b.legal_moves().len(), 8);
+    /// ```
+    pub fn new_variant(variant: &'static dyn Variant) -> Self {
+        Self::from_fen_variant(variant.start_fen(), variant)
+            .expect("the starting position of a variant is valid")
+    }
+
+    /// Returns the rules the game on this board is played by.
+    pub fn variant(&self) -> &'static dyn Variant {
+        self.variant
+    }
+
+    /// Returns the number of checks `side` has given. Only counted if the variant
+    /// needs it, and only since the board was set up.
+    pub fn checks_given(&self, side: piece::Side) -> u8 {
+        self.checks[side as usize]
+    }
+
+    /// Returns the number of pieces of type `t` in the hand of `side`, which can be
+    /// dropped in variants like Crazyhouse.
+    pub fn in_hand(&self, side: piece::Side, t: piece::Type) -> u8 {
+        self.hand[side as usize][hand_index(t)]
+    }
+
+    /// Returns true if castling is written as the king capturing its own rook, like in
+    /// Chess960 (`e1h1` instead of `e1g1`).
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
+    /// Chooses whether castling is written as the king capturing its own rook, like in
+    /// Chess960. Boards constructed with `new_chess960`, or from a FEN which can only be
+    /// a Chess960 position, use it already.
+    /// Should be called before any movement is performed.
+    pub fn set_chess960(&mut self, chess960: bool) {
+        self.chess960 = chess960;
     }
 
     /// Returns the side which should make the next move. (`current_move` field)
@@ -70,72 +367,265 @@ impl Board {
         self.current_move
     }
 
+    /// Returns the start and end position of the last performed movement,
+    /// or `None` if no movement was made yet.
+    pub fn last_move(&self) -> Option<(Pos, Pos)> {
+        self.history
+            .last()
+            .map(|u| (u.movement.start, u.movement.end))
+    }
+
+    /// Returns all movements performed on this board, oldest first.
+    pub fn moves_played(&self) -> Vec<Move> {
+        self.history.iter().map(|u| u.movement).collect()
+    }
+
+    /// Returns the piece captured by the last performed movement, if any.
+    pub fn last_captured(&self) -> Option<Piece> {
+        self.history.last().and_then(|u| u.captured).map(|(_, p)| p)
+    }
+
+    /// Returns the board as it was before the first of the `moves_played`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    ///
+    /// let mut b = Board::new();
+    /// b.make_move("e2e4".parse().unwrap()).unwrap();
+    ///
+    /// assert_eq!(b.start_position().to_fen(), Board::new().to_fen());
+    /// ```
+    pub fn start_position(&self) -> Board {
+        let mut board = self.clone();
+        while board.unmake_move().is_some() {}
+        board
+    }
+
+    /// Returns the field which can be captured en passant by the side to move, if any.
+    pub fn en_passant(&self) -> Option<Pos> {
+        self.en_passant
+    }
+
+    /// Returns the number of half-moves since the last capture or pawn movement.
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    /// Returns the number of the current full move, starting at 1 and incremented
+    /// after every movement of Black.
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    /// Returns a Zobrist key identifying the position: the pieces, the side to move,
+    /// the castling rights and the en passant field.
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+
+    /// Returns the key of the position in Polyglot opening books. It differs from `key`
+    /// if there are pieces in hand, which Polyglot doesn't know, and when the en passant
+    /// field is set but no pawn of the side to move stands next to the pawn which has
+    /// just moved, since Polyglot only includes the field then.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    ///
+    /// assert_eq!(Board::new().polyglot_key(), 0x463b96181691fc9c);
+    ///
+    /// // No black pawn can capture on e3
+    /// let b = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+    /// assert_eq!(b.polyglot_key(), 0x823c9b50fd114196);
+    ///
+    /// let b = Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3").unwrap();
+    /// assert_eq!(b.polyglot_key(), 0x22a48b5a8e47ff78);
+    /// ```
+    pub fn polyglot_key(&self) -> u64 {
+        let mut key = 0;
+        for side in [piece::Side::White, piece::Side::Black] {
+            for (pos, p) in self.pieces(side) {
+                key ^= zobrist::piece(p, pos);
+            }
+            for wing in [KING_SIDE, QUEEN_SIDE] {
+                if self.castling[side as usize][wing].is_some() {
+                    key ^= zobrist::castling(side, wing);
+                }
+            }
+        }
+        if let Some(pos) = self.en_passant {
+            // The pawn which has just moved stands one row further from its side
+            let rows = match self.current_move {
+                piece::Side::White => -1,
+                piece::Side::Black => 1,
+            };
+            let capturing = [-1, 1].into_iter().any(|columns| {
+                pos.offset(columns, rows)
+                    .and_then(|p| self.at(p))
+                    .is_some_and(|p| {
+                        p.p_side() == self.current_move
+                            && matches!(p.p_type(), piece::Type::Pawn(_))
+                    })
+            });
+            if capturing {
+                key ^= zobrist::en_passant(pos.column);
+            }
+        }
+        if self.current_move == piece::Side::White {
+            key ^= zobrist::side();
+        }
+        key
+    }
+
     /// Returns an optional at a specified position (`pos`).
     /// There is `Some(&Piece)` or `None` (specified position is empty)
     pub fn at(&self, pos: Pos) -> Option<&Piece> {
         self.fields[usize::from(pos)].as_ref()
     }
 
-    /// Returns an optional at a specified position (`pos`).
-    /// There is `Some(&mut Piece)` or `None` (specified position is empty).
-    /// This method is private and can be used instead of direct indexing
-    /// of the `fields` array.
-    fn at_mut(&mut self, pos: Pos) -> Option<&mut Piece> {
-        self.fields[usize::from(pos)].as_mut()
-    }
-
-    /// Prints the state of the board in a visually pleasing format
-    ///
-    /// # Example - board at its starting position
-    /// ```text
-    /// +---+---+---+---+---+---+---+---+
-    /// | ♜ | ♞ | ♝ | ♛ | ♚ | ♝ | ♞ | ♜ | 8
-    /// +---+---+---+---+---+---+---+---+
-    /// | ♟ | ♟ | ♟ | ♟ | ♟ | ♟ | ♟ | ♟ | 7
-    /// +---+---+---+---+---+---+---+---+
-    /// |   |   |   |   |   |   |   |   | 6
-    /// +---+---+---+---+---+---+---+---+
-    /// |   |   |   |   |   |   |   |   | 5
-    /// +---+---+---+---+---+---+---+---+
-    /// |   |   |   |   |   |   |   |   | 4
-    /// +---+---+---+---+---+---+---+---+
-    /// |   |   |   |   |   |   |   |   | 3
-    /// +---+---+---+---+---+---+---+---+
-    /// | ♟ | ♟ | ♟ | ♟ | ♟ | ♟ | ♟ | ♟ | 2
-    /// +---+---+---+---+---+---+---+---+
-    /// | ♜ | ♞ | ♝ | ♛ | ♚ | ♝ | ♞ | ♜ | 1
-    /// +---+---+---+---+---+---+---+---+
-    ///   a   b   c   d   e   f   g   h
+    /// Replaces the content of the field at `pos`, keeping the Zobrist key up to date.
+    /// This method is private and should be used instead of direct indexing
+    /// of the `fields` array when modifying it.
+    fn set(&mut self, pos: Pos, piece: Option<Piece>) {
+        if let Some(old) = self.fields[usize::from(pos)] {
+            self.key ^= zobrist::piece(&old, pos);
+        }
+        if let Some(new) = piece {
+            self.key ^= zobrist::piece(&new, pos);
+        }
+        self.fields[usize::from(pos)] = piece;
+    }
+
+    /// Returns the positions the piece at `pos` can legally move to.
+    /// The vector is empty if `pos` is an empty field, if the piece there does not belong
+    /// to the side which should make the next move, or if the piece has no legal moves.
+    ///
+    /// # Example
+    ///
     /// ```
-    pub fn print(&self) {
-        for row in (1..=8).rev() {
-            println!("+---+---+---+---+---+---+---+---+");
-            print!("|");
-            for column in 'a'..='h' {
-                if let Some(p) = self.at(Pos::new(column, row)) {
-                    let color = match p.p_side() {
-                        piece::Side::White => "\x1b[1;37m",
-                        piece::Side::Black => "\x1b[1;31m",
-                    };
-                    print!(" {}{}\x1b[0m |", color, p.p_type());
+    /// use rust_chess::core::board::Board;
+    /// use rust_chess::core::position::Pos;
+    ///
+    /// let b = Board::new();
+    ///
+    /// assert_eq!(b.legal_moves_from(Pos::new('b', 1)).len(), 2);
+    /// assert!(b.legal_moves_from(Pos::new('e', 7)).is_empty()); // Black can't move yet
+    /// ```
+    pub fn legal_moves_from(&self, pos: Pos) -> Vec<Pos> {
+        let mut ends = Vec::new();
+        for m in self.legal_moves() {
+            if m.start == pos && m.drop.is_none() && !ends.contains(&m.end) {
+                ends.push(m.end);
+            }
+        }
+        ends
+    }
+
+    /// Returns all legal movements of the side to move.
+    /// A pawn reaching the last row produces one movement for every possible promotion.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    ///
+    /// assert_eq!(Board::new().legal_moves().len(), 20);
+    /// ```
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for (start, p) in self.pieces(self.current_move) {
+            let last_row = match p.p_side() {
+                piece::Side::White => 8,
+                piece::Side::Black => 1,
+            };
+            let promotes = matches!(p.p_type(), piece::Type::Pawn(_));
+
+            for end in self.possible_moves(start) {
+                let movement = Move::new(start, end);
+                if self.variant.royal_king() && !self.is_safe(movement) {
+                    continue;
+                }
+                if promotes && end.row == last_row {
+                    for &t in self.variant.promotions() {
+                        moves.push(Move::with_promotion(start, end, t));
+                    }
                 } else {
-                    print!("   |");
+                    moves.push(movement);
                 }
             }
-            print!(" {}\n", row);
         }
-        println!("+---+---+---+---+---+---+---+---+");
+        if self.variant.drops() {
+            self.drop_moves(&mut moves);
+        }
+        self.variant.restrict(self, moves)
+    }
 
-        for column in 'a'..='h' {
-            print!("  {} ", column);
+    /// Returns true if `movement` captures a piece of the other side, also en passant.
+    pub fn is_capture(&self, movement: Move) -> bool {
+        let Some(moved) = self.at(movement.start) else {
+            return false;
+        };
+        match self.at(movement.end) {
+            Some(p) => p.p_side() != moved.p_side(),
+            None => {
+                matches!(moved.p_type(), piece::Type::Pawn(_))
+                    && Some(movement.end) == self.en_passant
+                    && movement.start.column != movement.end.column
+            }
         }
     }
 
-    /// Performs a piece movement by first calling `possible_moves(start_pos)` and checking if
-    /// `end_pos` is a valid movement. If it isn't, an explanatory `Err` is returned, else
+    /// Performs a piece movement by first calling `legal_moves_from(start_pos)` and checking
+    /// if `end_pos` is a valid movement. If it isn't, an explanatory `Err` is returned, else
     /// the movement is performed and `Ok` is returned.
+    /// A pawn reaching the last row is promoted to a queen.
     pub fn move_piece(&mut self, start_pos: Pos, end_pos: Pos) -> Result<(), &'static str> {
-        if let Some(p) = self.at(start_pos) {
+        let mut movement = Move::new(start_pos, end_pos);
+        if self.is_promotion(movement) {
+            movement.promotion = Some(piece::Type::Queen);
+        }
+        self.make_move(movement)
+    }
+
+    /// Performs `movement` if it is legal, else an explanatory `Err` is returned.
+    /// A pawn reaching the last row has to specify its promotion.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    /// use rust_chess::core::movement::Move;
+    /// use rust_chess::core::piece::Type;
+    /// use rust_chess::core::position::Pos;
+    /// use rust_chess::core::variant;
+    ///
+    /// let mut b = Board::new();
+    ///
+    /// assert!(b.make_move("e2e4".parse().unwrap()).is_ok());
+    /// assert!(b.make_move("e4e5".parse().unwrap()).is_err()); // Black moves now
+    ///
+    /// // A king is never held in hand
+    /// let mut b = Board::new_variant(variant::by_name("crazyhouse").unwrap());
+    /// assert!(b.make_move(Move::drop(Type::King, Pos::new('e', 4))).is_err());
+    /// ```
+    pub fn make_move(&mut self, movement: Move) -> Result<(), &'static str> {
+        if let Some(t) = movement.drop {
+            if t == piece::Type::King {
+                return Err("A king can't be dropped.");
+            }
+            if self.in_hand(self.current_move, t) == 0 {
+                return Err("The piece to drop isn't in hand.");
+            }
+            if !self.legal_moves().contains(&movement) {
+                return Err("Specified drop is impossible.");
+            }
+            self.make_move_unchecked(movement);
+            return Ok(());
+        }
+        if let Some(p) = self.at(movement.start) {
             if p.p_side() != self.current_move {
                 return Err("Wrong piece was selected.");
             }
@@ -143,32 +633,614 @@ impl Board {
             return Err("An empty field was selected.");
         }
 
-        let moves = self.possible_moves(start_pos);
-        if moves.contains(&end_pos) {
-            self.fields[usize::from(end_pos)] = self.fields[usize::from(start_pos)];
-            self.fields[usize::from(start_pos)] = None;
-
-            if let Some(p) = self.at_mut(end_pos) {
-                p.mark_moved();
+        if !self
+            .legal_moves_from(movement.start)
+            .contains(&movement.end)
+        {
+            return Err("Specified move is impossible.");
+        }
+        match movement.promotion {
+            None if self.is_promotion(movement) => {
+                return Err("Specified move requires a promotion.")
+            }
+            Some(t) if !self.variant.promotions().contains(&t) => {
+                return Err("Specified promotion is impossible.")
             }
+            Some(_) if !self.is_promotion(movement) => {
+                return Err("Specified move is not a promotion.")
+            }
+            _ => {}
+        }
 
-            self.current_move = if self.current_move == piece::Side::White {
-                piece::Side::Black
+        self.make_move_unchecked(movement);
+        Ok(())
+    }
+
+    /// Takes back the last performed movement and returns it,
+    /// or returns `None` if there is no movement to take back.
+    pub fn unmake_move(&mut self) -> Option<Move> {
+        let undo = self.history.pop()?;
+        let m = undo.movement;
+        let side = undo.moved.p_side();
+
+        if m.drop.is_some() {
+            self.fields[usize::from(m.end)] = None;
+        } else if let Some(wing) = self.castling_wing(undo.moved, m, &undo.castling) {
+            let (king_end, rook_end) = castling_columns(wing);
+            let rook_start = undo.castling[side as usize][wing].expect("castling needs a right");
+            let rook = self.fields[Pos::at(rook_end, m.start.row)];
+            self.fields[Pos::at(king_end, m.start.row)] = None;
+            self.fields[Pos::at(rook_end, m.start.row)] = None;
+            self.fields[Pos::at(rook_start, m.start.row)] = rook;
+            self.fields[usize::from(m.start)] = Some(undo.moved);
+        } else {
+            self.fields[usize::from(m.end)] = None;
+            self.fields[usize::from(m.start)] = Some(undo.moved);
+        }
+        if let Some((pos, captured)) = undo.captured {
+            self.fields[usize::from(pos)] = Some(captured);
+        }
+        for (pos, p) in undo.exploded {
+            self.fields[usize::from(pos)] = Some(p);
+        }
+
+        self.castling = undo.castling;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.checks = undo.checks;
+        self.hand = undo.hand;
+        self.promoted = undo.promoted;
+        if side == piece::Side::Black {
+            self.fullmove_number -= 1;
+        }
+        self.current_move = side;
+        self.key = undo.key;
+
+        Some(m)
+    }
+
+    /// Performs `movement` without verifying that it is legal.
+    /// Used by the engine, which only plays movements returned by `legal_moves`.
+    pub(crate) fn make_move_unchecked(&mut self, movement: Move) {
+        let side = self.current_move;
+        if let Some(t) = movement.drop {
+            let undo = self.undo(movement, Piece::new(t, side), None);
+            let count = self.hand[side as usize][hand_index(t)];
+            self.key ^= zobrist::hand(side, t, count);
+            self.hand[side as usize][hand_index(t)] = count - 1;
+
+            let is_pawn = matches!(t, piece::Type::Pawn(_));
+            let t = if is_pawn {
+                let rows = self.variant.double_step_rows(side);
+                piece::Type::Pawn(!rows.contains(&movement.end.row))
             } else {
-                piece::Side::White
+                t
             };
-            return Ok(());
+            self.set(movement.end, Some(Piece::new(t, side)));
+            self.end_turn(undo, is_pawn, None);
+            return;
+        }
+
+        let moved =
+            self.fields[usize::from(movement.start)].expect("a movement has to start at a piece");
+
+        let is_pawn = matches!(moved.p_type(), piece::Type::Pawn(_));
+        let captured_pos = if is_pawn
+            && Some(movement.end) == self.en_passant
+            && movement.start.column != movement.end.column
+        {
+            Pos::new(movement.end.column, movement.start.row)
         } else {
-            return Err("Specified move is impossible.");
+            movement.end
+        };
+
+        let captured = match self.at(captured_pos) {
+            // A king moving onto its own rook is castling
+            Some(p) if p.p_side() != side => Some((captured_pos, *p)),
+            _ => None,
+        };
+        let mut undo = self.undo(movement, moved, captured);
+
+        let mut piece = moved;
+        if let Some(t) = movement.promotion {
+            piece = Piece::new(t, side);
+        } else if !(is_pawn
+            && self
+                .variant
+                .double_step_rows(side)
+                .contains(&movement.end.row))
+        {
+            piece.mark_moved();
         }
+
+        if let Some(wing) = self.castling_wing(moved, movement, &self.castling) {
+            // The king and the rook may stand on each other's destination, so both are
+            // taken off the board first
+            let (king_end, rook_end) = castling_columns(wing);
+            let rook_start = Pos::new(
+                self.castling[side as usize][wing].expect("castling needs a right"),
+                movement.start.row,
+            );
+            let rook = self.fields[usize::from(rook_start)];
+            self.set(movement.start, None);
+            self.set(rook_start, None);
+            self.set(Pos::new(king_end, movement.start.row), Some(piece));
+            self.set(Pos::new(rook_end, movement.start.row), rook);
+        } else {
+            self.set(captured_pos, None);
+            self.set(movement.start, None);
+            self.set(movement.end, Some(piece));
+        }
+
+        if self.variant.explosions() && captured.is_some() {
+            // The capturing piece explodes together with all pieces around it but pawns
+            self.set(movement.end, None);
+            for pos in neighbours(movement.end) {
+                if let Some(p) = self.fields[usize::from(pos)] {
+                    if !matches!(p.p_type(), piece::Type::Pawn(_)) {
+                        undo.exploded.push((pos, p));
+                        self.set(pos, None);
+                    }
+                }
+            }
+        }
+
+        if self.variant.drops() {
+            // Captured pieces go to the hand, promoted ones as pawns
+            if let Some((pos, p)) = captured {
+                let t = if self.promoted & bit(pos) != 0 {
+                    piece::Type::Pawn(false)
+                } else {
+                    p.p_type()
+                };
+                let count = self.hand[side as usize][hand_index(t)] + 1;
+                self.hand[side as usize][hand_index(t)] = count;
+                self.key ^= zobrist::hand(side, t, count);
+            }
+            let was_promoted = self.promoted & bit(movement.start) != 0;
+            self.promoted &= !(bit(movement.start) | bit(captured_pos));
+            if was_promoted || movement.promotion.is_some() {
+                self.promoted |= bit(movement.end);
+            }
+        }
+
+        // Castling rights are lost by moving the king or the rook,
+        // or when the rook is captured or either of them explodes
+        for s in [piece::Side::White, piece::Side::Black] {
+            let row = home_row(s);
+            for wing in [KING_SIDE, QUEEN_SIDE] {
+                if let Some(column) = self.castling[s as usize][wing] {
+                    let rook = Pos::new(column, row);
+                    let king_moved = s == side && moved.p_type() == piece::Type::King;
+                    let exploded = undo
+                        .exploded
+                        .iter()
+                        .any(|&(pos, p)| pos == rook || p == Piece::new(piece::Type::King, s));
+                    if king_moved || movement.start == rook || movement.end == rook || exploded {
+                        self.castling[s as usize][wing] = None;
+                        self.key ^= zobrist::castling(s, wing);
+                    }
+                }
+            }
+        }
+
+        let en_passant =
+            (is_pawn && movement.start.row.abs_diff(movement.end.row) == 2).then(|| {
+                Pos::new(
+                    movement.start.column,
+                    (movement.start.row + movement.end.row) / 2,
+                )
+            });
+        let irreversible = is_pawn || undo.captured.is_some();
+        self.end_turn(undo, irreversible, en_passant);
+    }
+
+    /// Returns what is needed to take back `movement` of the piece `moved`, which captures
+    /// `captured`, from the current state.
+    fn undo(&self, movement: Move, moved: Piece, captured: Option<(Pos, Piece)>) -> Undo {
+        Undo {
+            movement,
+            moved,
+            captured,
+            exploded: Vec::new(),
+            castling: self.castling,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            checks: self.checks,
+            hand: self.hand,
+            promoted: self.promoted,
+            key: self.key,
+        }
+    }
+
+    /// Finishes a movement after the pieces were moved: sets the new `en_passant` field,
+    /// updates the clocks, resetting the half-move clock after an `irreversible` movement,
+    /// passes the turn to the other side and stores `undo` in the history.
+    fn end_turn(&mut self, undo: Undo, irreversible: bool, en_passant: Option<Pos>) {
+        let side = self.current_move;
+        if let Some(pos) = self.en_passant {
+            self.key ^= zobrist::en_passant(pos.column);
+        }
+        self.en_passant = en_passant;
+        if let Some(pos) = en_passant {
+            self.key ^= zobrist::en_passant(pos.column);
+        }
+
+        if irreversible {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if side == piece::Side::Black {
+            self.fullmove_number += 1;
+        }
+        self.current_move = side.opposite();
+        self.key ^= zobrist::side();
+        if self.variant.counts_checks() && self.in_check() {
+            self.checks[side as usize] += 1;
+        }
+        self.history.push(undo);
+    }
+
+    /// Returns true if the side to move is in check.
+    /// A king which isn't royal, as in Antichess, is never in check. In Atomic chess
+    /// a king next to the other king isn't in check, as capturing it would explode both.
+    pub fn in_check(&self) -> bool {
+        let side = self.current_move;
+        self.variant.royal_king()
+            && self
+                .king(side)
+                .is_some_and(|king| attacked(&self.fields, king, side.opposite()))
+            && !(self.variant.explosions() && self.kings_touch(&self.fields))
+    }
+
+    /// Returns the state of the game.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::{Board, Status};
+    /// use rust_chess::core::piece::Side;
+    ///
+    /// let mut b = Board::new();
+    /// for m in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+    ///     b.make_move(m.parse().unwrap()).unwrap();
+    /// }
+    ///
+    /// assert_eq!(b.status(), Status::Checkmate(Side::Black));
+    /// ```
+    pub fn status(&self) -> Status {
+        if let Some(status) = self.variant.outcome(self) {
+            status
+        } else if self.legal_moves().is_empty() {
+            self.variant.no_moves(self)
+        } else if self.halfmove_clock >= 100 {
+            Status::FiftyMoveRule
+        } else if self.repetitions() >= 2 {
+            Status::Repetition
+        } else if self.variant.draws_on_insufficient_material() && self.insufficient_material() {
+            Status::InsufficientMaterial
+        } else {
+            Status::Ongoing
+        }
+    }
+
+    /// Returns how many times the current position occurred before.
+    /// Only positions since the last capture or pawn movement are considered.
+    pub fn repetitions(&self) -> usize {
+        self.history
+            .iter()
+            .rev()
+            .take(self.halfmove_clock as usize)
+            .filter(|u| u.key == self.key)
+            .count()
+    }
+
+    /// Counts the leaf nodes of the tree of legal movements `depth` half-moves deep.
+    /// Used to verify the movement generation against known values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    ///
+    /// assert_eq!(Board::new().perft(3), 8902);
+    /// ```
+    pub fn perft(&self, depth: u32) -> u64 {
+        self.clone().perft_recursive(depth)
+    }
+
+    /// Like `perft`, but returns the number of leaf nodes after every legal movement.
+    pub fn perft_divide(&self, depth: u32) -> Vec<(Move, u64)> {
+        let mut board = self.clone();
+        let mut result = Vec::new();
+        for m in self.legal_moves() {
+            board.make_move_unchecked(m);
+            result.push((m, board.perft_recursive(depth.saturating_sub(1))));
+            board.unmake_move();
+        }
+        result
+    }
+
+    fn perft_recursive(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        let mut nodes = 0;
+        for m in moves {
+            self.make_move_unchecked(m);
+            nodes += self.perft_recursive(depth - 1);
+            self.unmake_move();
+        }
+        nodes
+    }
+
+    /// Returns true if `movement` moves a pawn to the last row.
+    pub fn is_promotion(&self, movement: Move) -> bool {
+        match self.at(movement.start) {
+            Some(p) if matches!(p.p_type(), piece::Type::Pawn(_)) => {
+                movement.end.row == home_row(p.p_side().opposite())
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns true if the king of the moving side isn't attacked after `movement`.
+    /// The movement has to be one of the `possible_moves`.
+    fn is_safe(&self, movement: Move) -> bool {
+        let Some(moved) = self.at(movement.start).copied() else {
+            return false;
+        };
+        let side = moved.p_side();
+        let mut fields = *self.fields;
+
+        if matches!(moved.p_type(), piece::Type::Pawn(_))
+            && Some(movement.end) == self.en_passant
+            && movement.start.column != movement.end.column
+        {
+            fields[Pos::at(movement.end.column, movement.start.row)] = None;
+        }
+        fields[usize::from(movement.start)] = None;
+        let mut king_end = movement.end;
+        if let Some(wing) = self.castling_wing(moved, movement, &self.castling) {
+            let (king_column, rook_column) = castling_columns(wing);
+            let rook_start = self.castling[side as usize][wing].expect("castling needs a right");
+            let row = movement.start.row;
+            king_end = Pos::new(king_column, row);
+            fields[Pos::at(rook_start, row)] = None;
+            fields[Pos::at(rook_column, row)] = Some(Piece::new(piece::Type::Rook, side));
+        }
+        fields[usize::from(king_end)] = Some(moved);
+
+        if self.variant.explosions() {
+            if self.is_capture(movement) {
+                fields[usize::from(movement.end)] = None;
+                for pos in neighbours(movement.end) {
+                    if fields[usize::from(pos)]
+                        .is_some_and(|p| !matches!(p.p_type(), piece::Type::Pawn(_)))
+                    {
+                        fields[usize::from(pos)] = None;
+                    }
+                }
+            }
+            // The own king may not explode, exploding the other one wins at once
+            let own = find_king(&fields, side);
+            let other = find_king(&fields, side.opposite());
+            return match (own, other) {
+                (None, _) => false,
+                (Some(_), None) => true,
+                (Some(king), Some(_)) => {
+                    self.kings_touch(&fields) || !attacked(&fields, king, side.opposite())
+                }
+            };
+        }
+
+        let king = if moved.p_type() == piece::Type::King {
+            Some(king_end)
+        } else {
+            self.king(side)
+        };
+        king.is_none_or(|king| !attacked(&fields, king, side.opposite()))
+    }
+
+    /// Returns true if the kings of both sides stand next to each other on `fields`.
+    fn kings_touch(&self, fields: &[Option<Piece>; 64]) -> bool {
+        match (
+            find_king(fields, piece::Side::White),
+            find_king(fields, piece::Side::Black),
+        ) {
+            (Some(white), Some(black)) => neighbours(white).any(|pos| pos == black),
+            _ => false,
+        }
+    }
+
+    /// Returns the position of the king of `side`.
+    pub(crate) fn king(&self, side: piece::Side) -> Option<Pos> {
+        self.pieces(side)
+            .find(|(_, p)| p.p_type() == piece::Type::King)
+            .map(|(pos, _)| pos)
+    }
+
+    /// Returns an iterator over all pieces of `side` together with their positions.
+    pub(crate) fn pieces(&self, side: piece::Side) -> impl Iterator<Item = (Pos, &Piece)> {
+        ('a'..='h')
+            .flat_map(|column| (1..=8).map(move |row| Pos::new(column, row)))
+            .filter_map(|pos| self.at(pos).map(|p| (pos, p)))
+            .filter(move |(_, p)| p.p_side() == side)
+    }
+
+    /// Returns the castling destinations of the king at `pos`.
+    /// The king has to be on its home row with the castling rook, the fields between them
+    /// have to be empty and the king may not be in check or pass an attacked field.
+    fn castling_moves(&self, pos: Pos) -> Vec<Pos> {
+        let mut moves = Vec::new();
+        let Some(king) = self.at(pos) else {
+            return moves;
+        };
+        let side = king.p_side();
+        let row = home_row(side);
+        if pos.row != row {
+            return moves;
+        }
+
+        for wing in [KING_SIDE, QUEEN_SIDE] {
+            let Some(rook_column) = self.castling[side as usize][wing] else {
+                continue;
+            };
+            let rook = Pos::new(rook_column, row);
+            if self.at(rook) != Some(&Piece::new(piece::Type::Rook, side)) {
+                continue;
+            }
+            let (king_end, rook_end) = castling_columns(wing);
+
+            // Every field the king and the rook pass or land on has to be empty
+            let columns = [pos.column, rook_column, king_end, rook_end];
+            let first = *columns.iter().min().unwrap();
+            let last = *columns.iter().max().unwrap();
+            let empty = (first..=last)
+                .map(|column| Pos::new(column, row))
+                .all(|f| f == pos || f == rook || self.at(f).is_none());
+
+            let (from, to) = (pos.column.min(king_end), pos.column.max(king_end));
+            let safe = (from..=to)
+                .all(|column| !attacked(&self.fields, Pos::new(column, row), side.opposite()));
+
+            if empty && safe {
+                moves.push(if self.chess960 {
+                    rook
+                } else {
+                    Pos::new(king_end, row)
+                });
+            }
+        }
+        moves
+    }
+
+    /// Adds the drops of the pieces in the hand of the side to move onto the empty fields
+    /// to `moves`. Pawns can't be dropped on the first or the last row, and in check only
+    /// drops which block it are legal.
+    fn drop_moves(&self, moves: &mut Vec<Move>) {
+        let side = self.current_move;
+        let checked_king = self.king(side).filter(|_| self.in_check());
+        for t in IN_HAND {
+            if self.in_hand(side, t) == 0 {
+                continue;
+            }
+            let pawn = matches!(t, piece::Type::Pawn(_));
+            for column in 'a'..='h' {
+                for row in 1..=8 {
+                    let end = Pos::new(column, row);
+                    if self.at(end).is_some() || pawn && (row == 1 || row == 8) {
+                        continue;
+                    }
+                    if let Some(king) = checked_king {
+                        let mut fields = *self.fields;
+                        fields[usize::from(end)] = Some(Piece::new(t, side));
+                        if attacked(&fields, king, side.opposite()) {
+                            continue;
+                        }
+                    }
+                    moves.push(Move::drop(t, end));
+                }
+            }
+        }
+    }
+
+    /// Returns the wing if `movement` of the piece `moved` is castling, given the castling
+    /// rights before the movement.
+    fn castling_wing(
+        &self,
+        moved: Piece,
+        movement: Move,
+        castling: &[[Option<char>; 2]; 2],
+    ) -> Option<usize> {
+        let side = moved.p_side();
+        let row = home_row(side);
+        if moved.p_type() != piece::Type::King
+            || movement.start.row != row
+            || movement.end.row != row
+        {
+            return None;
+        }
+        let wing = if movement.end.column > movement.start.column {
+            KING_SIDE
+        } else {
+            QUEEN_SIDE
+        };
+        let rook_column = castling[side as usize][wing]?;
+
+        let castles = if self.chess960 {
+            movement.end.column == rook_column
+        } else {
+            (movement.start.column as u8).abs_diff(movement.end.column as u8) == 2
+        };
+        castles.then_some(wing)
     }
-    /// Returns possible positions for a piece at `pos` as a vector.
+
+    /// Returns true if neither side can possibly checkmate: only kings and at most a single
+    /// knight or bishop remain, or only bishops on fields of the same colour.
+    fn insufficient_material(&self) -> bool {
+        let mut minors = 0;
+        let mut bishop_colors = [false; 2];
+        for (pos, p) in self
+            .pieces(piece::Side::White)
+            .chain(self.pieces(piece::Side::Black))
+        {
+            match p.p_type() {
+                piece::Type::King => {}
+                piece::Type::Knight => minors += 1,
+                piece::Type::Bishop => {
+                    bishop_colors[((pos.column as u8 - b'a' + pos.row) % 2) as usize] = true
+                }
+                _ => return false,
+            }
+        }
+        let bishops_one_color = !(bishop_colors[0] && bishop_colors[1]);
+        minors == 0 && bishops_one_color || minors == 1 && !bishop_colors.contains(&true)
+    }
+
+    /// Computes the Zobrist key of the position from scratch.
+    fn compute_key(&self) -> u64 {
+        let mut key = 0;
+        for side in [piece::Side::White, piece::Side::Black] {
+            for (pos, p) in self.pieces(side) {
+                key ^= zobrist::piece(p, pos);
+            }
+            for wing in [KING_SIDE, QUEEN_SIDE] {
+                if self.castling[side as usize][wing].is_some() {
+                    key ^= zobrist::castling(side, wing);
+                }
+            }
+            for t in IN_HAND {
+                for count in 1..=self.in_hand(side, t) {
+                    key ^= zobrist::hand(side, t, count);
+                }
+            }
+        }
+        if let Some(pos) = self.en_passant {
+            key ^= zobrist::en_passant(pos.column);
+        }
+        if self.current_move == piece::Side::White {
+            key ^= zobrist::side();
+        }
+        key
+    }
+
+    /// Returns possible positions for a piece at `pos` as a vector, regardless of whether
+    /// its side should make the next move. The movements might leave the own king in check,
+    /// which is verified by `is_safe`.
     /// The vector is empty if an empty field was selected or when the specified piece
     /// has no possible moves.
     fn possible_moves(&self, pos: Pos) -> Vec<Pos> {
         let mut moves: Vec<Pos> = Vec::new();
 
         if let Some(p) = self.at(pos) {
+            let side = p.p_side();
             match p.p_type() {
                 piece::Type::King => {
                     let c1: char = (pos.column as u8 - 1).into();
@@ -179,7 +1251,7 @@ impl Board {
                             if cur != pos && cur.is_valid() {
                                 match self.at(cur) {
                                     Some(p) => {
-                                        if p.p_side() != self.current_move {
+                                        if p.p_side() != side {
                                             moves.push(cur);
                                         }
                                     }
@@ -188,13 +1260,16 @@ impl Board {
                             }
                         }
                     }
+                    if self.variant.castling() {
+                        moves.extend(self.castling_moves(pos));
+                    }
                 }
                 piece::Type::Queen => {
                     // Rook code
                     for r in pos.row + 1..=8 {
                         let cur = Pos::new(pos.column, r);
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -205,7 +1280,7 @@ impl Board {
                     for r in (1..pos.row).rev() {
                         let cur = Pos::new(pos.column, r);
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -216,7 +1291,7 @@ impl Board {
                     for c in (pos.column as u8 + 1).into()..='h' {
                         let cur = Pos::new(c, pos.row);
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -227,7 +1302,7 @@ impl Board {
                     for c in ('a'..pos.column).rev() {
                         let cur = Pos::new(c, pos.row);
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -242,7 +1317,7 @@ impl Board {
                             break;
                         }
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -256,7 +1331,7 @@ impl Board {
                             break;
                         }
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -270,7 +1345,7 @@ impl Board {
                             break;
                         }
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -284,7 +1359,7 @@ impl Board {
                             break;
                         }
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -297,7 +1372,7 @@ impl Board {
                     for r in pos.row + 1..=8 {
                         let cur = Pos::new(pos.column, r);
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -308,7 +1383,7 @@ impl Board {
                     for r in (1..pos.row).rev() {
                         let cur = Pos::new(pos.column, r);
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -319,7 +1394,7 @@ impl Board {
                     for c in (pos.column as u8 + 1).into()..='h' {
                         let cur = Pos::new(c, pos.row);
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -330,7 +1405,7 @@ impl Board {
                     for c in ('a'..pos.column).rev() {
                         let cur = Pos::new(c, pos.row);
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -346,7 +1421,7 @@ impl Board {
                             break;
                         }
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -360,7 +1435,7 @@ impl Board {
                             break;
                         }
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -374,7 +1449,7 @@ impl Board {
                             break;
                         }
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -388,7 +1463,7 @@ impl Board {
                             break;
                         }
                         if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                            if p.p_side() != side {
                                 moves.push(cur);
                             }
                             break;
@@ -412,7 +1487,7 @@ impl Board {
                     for cur in p {
                         if cur.is_valid() {
                             if let Some(p) = self.at(cur) {
-                                if p.p_side() != self.current_move {
+                                if p.p_side() != side {
                                     moves.push(cur);
                                 }
                             } else {
@@ -423,7 +1498,7 @@ impl Board {
                 }
                 piece::Type::Pawn(has_moved) => {
                     let cur_relative = |c: u8, r: u8, subtract: bool| {
-                        let mut cur = match self.current_move {
+                        let mut cur = match side {
                             piece::Side::White => Pos::new(pos.column, pos.row + r),
                             piece::Side::Black => Pos::new(pos.column, pos.row - r),
                         };
@@ -436,33 +1511,26 @@ impl Board {
                     };
 
                     let cur = cur_relative(0, 1, false);
-                    if cur.is_valid() {
-                        if let None = self.at(cur) {
-                            moves.push(cur);
-                        }
-                    }
-                    let cur = cur_relative(1, 1, true);
-                    if cur.is_valid() {
-                        if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
-                                moves.push(cur);
-                            }
-                        }
+                    let blocked = !cur.is_valid() || self.at(cur).is_some();
+                    if !blocked {
+                        moves.push(cur);
                     }
-                    let cur = cur_relative(1, 1, false);
-                    if cur.is_valid() {
-                        if let Some(p) = self.at(cur) {
-                            if p.p_side() != self.current_move {
+                    for subtract in [true, false] {
+                        let cur = cur_relative(1, 1, subtract);
+                        if cur.is_valid() {
+                            if let Some(p) = self.at(cur) {
+                                if p.p_side() != side {
+                                    moves.push(cur);
+                                }
+                            } else if side == self.current_move && self.en_passant == Some(cur) {
                                 moves.push(cur);
                             }
                         }
                     }
-                    if !has_moved {
+                    if !has_moved && !blocked {
                         let cur = cur_relative(0, 2, false);
-                        if cur.is_valid() {
-                            if let None = self.at(cur) {
-                                moves.push(cur);
-                            }
+                        if cur.is_valid() && self.at(cur).is_none() {
+                            moves.push(cur);
                         }
                     }
                 }
@@ -474,3 +1542,75 @@ impl Board {
         moves
     }
 }
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the row on which the pieces of `side` start.
+fn home_row(side: piece::Side) -> u8 {
+    match side {
+        piece::Side::White => 1,
+        piece::Side::Black => 8,
+    }
+}
+
+/// Returns the index of `t` in `IN_HAND`.
+fn hand_index(t: piece::Type) -> usize {
+    match t {
+        piece::Type::Pawn(_) => 0,
+        piece::Type::Knight => 1,
+        piece::Type::Bishop => 2,
+        piece::Type::Rook => 3,
+        piece::Type::Queen => 4,
+        piece::Type::King => unreachable!("a king is never held in hand"),
+    }
+}
+
+/// Returns the up to 8 fields around `pos`.
+fn neighbours(pos: Pos) -> impl Iterator<Item = Pos> {
+    ORTHOGONAL
+        .into_iter()
+        .chain(DIAGONAL)
+        .filter_map(move |(c, r)| pos.offset(c, r))
+}
+
+/// Returns the position of the king of `side` on `fields`, if any.
+fn find_king(fields: &[Option<Piece>; 64], side: piece::Side) -> Option<Pos> {
+    let index = fields
+        .iter()
+        .position(|&p| p == Some(Piece::new(piece::Type::King, side)))?;
+    Some(Pos::new(
+        (b'a' + index as u8 % 8).into(),
+        index as u8 / 8 + 1,
+    ))
+}
+
+/// Returns the bit of `pos` in a set of fields like `Board::promoted`.
+fn bit(pos: Pos) -> u64 {
+    1 << usize::from(pos)
+}
+
+/// Returns the columns the king and the rook end on when castling on `wing`.
+fn castling_columns(wing: usize) -> (char, char) {
+    if wing == KING_SIDE {
+        ('g', 'f')
+    } else {
+        ('c', 'd')
+    }
+}
+
+const KNIGHT_JUMPS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const ORTHOGONAL: [(i8, i8); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+const DIAGONAL: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
@@ -0,0 +1,259 @@
+//! Attacks of the pieces: which pieces could capture on a field. They decide whether a
+//! king is in check or may castle, and can be queried to evaluate exchanges or to show
+//! which side controls the fields.
+
+use crate::core::movement::Move;
+use crate::core::piece::{Piece, Side, Type};
+use crate::core::position::Pos;
+
+use super::{neighbours, Board, DIAGONAL, KNIGHT_JUMPS, ORTHOGONAL};
+
+/// Number of pieces of each side attacking every field, see `Board::control`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Control {
+    /// Indexed by side and then by field
+    counts: [[u8; 64]; 2],
+}
+
+impl Control {
+    /// Returns the number of pieces of `side` attacking `pos`.
+    pub fn count(&self, pos: Pos, side: Side) -> u8 {
+        self.counts[side as usize][usize::from(pos)]
+    }
+
+    /// Returns the side attacking `pos` with more pieces, or `None` if both sides
+    /// attack it equally often.
+    pub fn owner(&self, pos: Pos) -> Option<Side> {
+        let white = self.count(pos, Side::White);
+        let black = self.count(pos, Side::Black);
+        match white.cmp(&black) {
+            std::cmp::Ordering::Greater => Some(Side::White),
+            std::cmp::Ordering::Less => Some(Side::Black),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+impl Board {
+    /// Returns the fields of the pieces of `side` attacking `pos`, that is the pieces
+    /// which could capture a piece of the other side standing there, whether or not
+    /// the field is occupied. Pinned pieces attack as well, pawns only diagonally
+    /// and en passant isn't taken into account. A piece behind another one on the
+    /// same line, like a rook behind a rook, doesn't attack until the front one moves.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    /// use rust_chess::core::piece::Side;
+    /// use rust_chess::core::position::Pos;
+    ///
+    /// let b = Board::new();
+    ///
+    /// let attackers = b.attackers_of(Pos::new('f', 3), Side::White);
+    /// assert_eq!(attackers, vec![Pos::new('e', 2), Pos::new('g', 2), Pos::new('g', 1)]);
+    /// assert!(!b.is_attacked(Pos::new('e', 4), Side::White));
+    /// ```
+    pub fn attackers_of(&self, pos: Pos, side: Side) -> Vec<Pos> {
+        attackers(&self.fields, pos, side)
+    }
+
+    /// Returns true if any piece of `side` attacks `pos`, see `attackers_of`.
+    pub fn is_attacked(&self, pos: Pos, side: Side) -> bool {
+        attacked(&self.fields, pos, side)
+    }
+
+    /// Returns how many pieces of each side attack every field.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    /// use rust_chess::core::piece::Side;
+    /// use rust_chess::core::position::Pos;
+    ///
+    /// let control = Board::new().control();
+    ///
+    /// assert_eq!(control.count(Pos::new('d', 3), Side::White), 2);
+    /// assert_eq!(control.owner(Pos::new('d', 6)), Some(Side::Black));
+    /// assert_eq!(control.owner(Pos::new('e', 4)), None);
+    /// ```
+    pub fn control(&self) -> Control {
+        let mut counts = [[0; 64]; 2];
+        for side in [Side::White, Side::Black] {
+            for (index, count) in counts[side as usize].iter_mut().enumerate() {
+                let pos = Pos::new((b'a' + index as u8 % 8).into(), index as u8 / 8 + 1);
+                *count = attackers(&self.fields, pos, side).len() as u8;
+            }
+        }
+        Control { counts }
+    }
+
+    /// Static exchange evaluation: returns the material in centipawns, see `Type::value`,
+    /// the side to move wins by `movement` if both sides then keep capturing on its end
+    /// field with their cheapest piece for as long as it pays off. Pieces lined up
+    /// behind others, like a rook behind a queen, join the exchange when the way
+    /// becomes free. A movement which captures nothing returns 0, or less if the moved
+    /// piece can be won. Pins and the special rules of variants are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    ///
+    /// let b = Board::from_fen("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1").unwrap();
+    /// assert_eq!(b.see(&"e1e5".parse().unwrap()), 100);
+    ///
+    /// let b = Board::from_fen("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1")
+    ///     .unwrap();
+    /// assert_eq!(b.see(&"d3e5".parse().unwrap()), -200);
+    /// ```
+    pub fn see(&self, movement: &Move) -> i32 {
+        let mut fields = *self.fields;
+        let side = self.current_move;
+        let moved = match movement.drop {
+            Some(t) => Piece::new(t, side),
+            None => match self.at(movement.start) {
+                Some(&p) if p.p_side() == side => p,
+                _ => return 0,
+            },
+        };
+        let own = self.at(movement.end).is_some_and(|p| p.p_side() == side);
+        if own || (moved.p_type() == Type::King && !self.is_capture(*movement)) {
+            // Castling never loses material
+            return 0;
+        }
+
+        // `gains[i]` is the material won by the side making the i-th capture
+        // if the exchange stopped after it
+        let mut gains = Vec::with_capacity(32);
+        let mut gain = match fields[usize::from(movement.end)] {
+            Some(p) => p.p_type().value(),
+            None if self.is_capture(*movement) => {
+                let pawn = Pos::new(movement.end.column, movement.start.row);
+                fields[usize::from(pawn)] = None;
+                Type::Pawn(false).value()
+            }
+            None => 0,
+        };
+        let mut on_field = moved.p_type().value();
+        if let Some(t) = movement.promotion {
+            gain += t.value() - moved.p_type().value();
+            on_field = t.value();
+        }
+        gains.push(gain);
+        if movement.drop.is_none() {
+            fields[usize::from(movement.start)] = None;
+        }
+
+        let mut turn = side.opposite();
+        while let Some((at, p)) = cheapest_attacker(&fields, movement.end, turn) {
+            // The king may only capture a piece which isn't protected anymore
+            if p.p_type() == Type::King
+                && cheapest_attacker(&fields, movement.end, turn.opposite()).is_some()
+            {
+                break;
+            }
+            gains.push(on_field - gains[gains.len() - 1]);
+            on_field = p.p_type().value();
+            fields[usize::from(at)] = None;
+            turn = turn.opposite();
+        }
+
+        // Either side may stop capturing when continuing would lose material
+        while gains.len() > 1 {
+            let last = gains.pop().unwrap();
+            let previous = gains.last_mut().unwrap();
+            *previous = (*previous).min(-last);
+        }
+        gains[0]
+    }
+}
+
+/// Returns the field and the piece of the cheapest piece of `side` on `fields`
+/// attacking `pos`, the king last.
+fn cheapest_attacker(fields: &[Option<Piece>; 64], pos: Pos, side: Side) -> Option<(Pos, Piece)> {
+    attackers(fields, pos, side)
+        .into_iter()
+        .filter_map(|at| fields[usize::from(at)].map(|p| (at, p)))
+        .min_by_key(|(_, p)| match p.p_type() {
+            Type::King => i32::MAX,
+            t => t.value(),
+        })
+}
+
+/// Returns true if any piece of `side` on `fields` could capture a piece at `pos`.
+pub(super) fn attacked(fields: &[Option<Piece>; 64], pos: Pos, side: Side) -> bool {
+    visit_attackers(fields, pos, side, |_| true)
+}
+
+/// Returns the fields of the pieces of `side` on `fields` which could capture a piece
+/// at `pos`.
+fn attackers(fields: &[Option<Piece>; 64], pos: Pos, side: Side) -> Vec<Pos> {
+    let mut attackers = Vec::new();
+    visit_attackers(fields, pos, side, |at| {
+        attackers.push(at);
+        false
+    });
+    attackers
+}
+
+/// Calls `stop` with the field of every piece of `side` on `fields` which could capture
+/// a piece at `pos`: pawns first, then knights, the king and the sliding pieces.
+/// Returns true as soon as `stop` does, else false.
+fn visit_attackers(
+    fields: &[Option<Piece>; 64],
+    pos: Pos,
+    side: Side,
+    mut stop: impl FnMut(Pos) -> bool,
+) -> bool {
+    let mut visit = |at: Option<Pos>, types: &[Type]| {
+        at.filter(|&at| {
+            fields[usize::from(at)]
+                .is_some_and(|p| p.p_side() == side && types.contains(&p.p_type()))
+        })
+        .is_some_and(&mut stop)
+    };
+
+    // Pawns attack towards the opposite side, so look backwards from `pos`
+    let pawn_row: i8 = match side {
+        Side::White => -1,
+        Side::Black => 1,
+    };
+    let pawns = [Type::Pawn(false), Type::Pawn(true)];
+    if visit(pos.offset(-1, pawn_row), &pawns) || visit(pos.offset(1, pawn_row), &pawns) {
+        return true;
+    }
+
+    if KNIGHT_JUMPS
+        .iter()
+        .any(|&(c, r)| visit(pos.offset(c, r), &[Type::Knight]))
+    {
+        return true;
+    }
+
+    if neighbours(pos).any(|at| visit(Some(at), &[Type::King])) {
+        return true;
+    }
+
+    let sliders = [
+        (ORTHOGONAL, [Type::Rook, Type::Queen]),
+        (DIAGONAL, [Type::Bishop, Type::Queen]),
+    ];
+    for (directions, types) in sliders {
+        for (c, r) in directions {
+            let mut cur = pos.offset(c, r);
+            while let Some(at) = cur {
+                if fields[usize::from(at)].is_some() {
+                    if visit(cur, &types) {
+                        return true;
+                    }
+                    break;
+                }
+                cur = at.offset(c, r);
+            }
+        }
+    }
+
+    false
+}
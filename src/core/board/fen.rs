@@ -0,0 +1,349 @@
+use crate::core::piece::{Piece, Side, Type};
+use crate::core::position::Pos;
+use crate::core::variant::{self, Variant};
+
+use super::{attacked, bit, hand_index, home_row, Board, IN_HAND, KING_SIDE, QUEEN_SIDE};
+
+/// FEN of the standard starting position
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+impl Board {
+    /// Constructs a `Board` from a position in Forsyth-Edwards Notation.
+    /// The half-move clock and the full move number may be omitted.
+    /// Castling rights may also be given by the columns of the rooks, like `HAha`,
+    /// as in X-FEN and Shredder-FEN. A position where the king or a rook which can castle
+    /// doesn't stand on its standard field is treated as a Chess960 position.
+    /// If the position can't be parsed or isn't valid an explanatory `Err` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    /// use rust_chess::core::piece::Side;
+    ///
+    /// let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+    /// let b = Board::from_fen(fen).unwrap();
+    ///
+    /// assert_eq!(b.current_move(), Side::Black);
+    /// assert_eq!(b.to_fen(), fen);
+    ///
+    /// assert!(Board::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").is_err()); // no kings
+    /// ```
+    pub fn from_fen(fen: &str) -> Result<Board, &'static str> {
+        Board::from_fen_variant(fen, &variant::Standard)
+    }
+
+    /// Like `from_fen`, but the position is verified and played by the rules of `variant`.
+    /// In variants with drops the pieces in hand follow the placement in brackets, like
+    /// `[Nbp]`, and promoted pieces are marked with `~`, like `Q~`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    /// use rust_chess::core::variant::Antichess;
+    ///
+    /// let fen = "8/8/8/8/8/5n2/8/4N3 w - - 0 1";
+    /// assert!(Board::from_fen(fen).is_err()); // no kings
+    ///
+    /// let b = Board::from_fen_variant(fen, &Antichess).unwrap();
+    /// assert_eq!(b.legal_moves(), ["e1f3".parse().unwrap()]); // capturing is compulsory
+    /// ```
+    pub fn from_fen_variant(
+        fen: &str,
+        variant: &'static dyn Variant,
+    ) -> Result<Board, &'static str> {
+        let mut parts = fen.split_whitespace();
+        let placement = parts.next().ok_or("FEN is empty.")?;
+        let side = parts.next().ok_or("FEN lacks the side to move.")?;
+        let castling = parts.next().ok_or("FEN lacks the castling rights.")?;
+        let en_passant = parts.next().ok_or("FEN lacks the en passant field.")?;
+        let halfmove_clock = parts.next().unwrap_or("0");
+        let fullmove_number = parts.next().unwrap_or("1");
+        if parts.next().is_some() {
+            return Err("FEN has too many fields.");
+        }
+
+        let (placement, pocket) = match placement.split_once('[') {
+            Some((placement, pocket)) => {
+                let pocket = pocket
+                    .strip_suffix(']')
+                    .ok_or("FEN has an unclosed pocket.")?;
+                (placement, Some(pocket))
+            }
+            None => (placement, None),
+        };
+        if !variant.drops() && (pocket.is_some() || placement.contains('~')) {
+            return Err("FEN has pieces in hand, but the variant has no drops.");
+        }
+
+        let mut fields = Box::new([None; 64]);
+        let mut promoted = 0;
+        let rows: Vec<&str> = placement.split('/').collect();
+        if rows.len() != 8 {
+            return Err("FEN has to describe 8 rows.");
+        }
+        for (text, row) in rows.iter().zip((1..=8).rev()) {
+            let mut column = b'a';
+            for c in text.chars() {
+                if c == '~' {
+                    let pos = Pos::new((column - 1).into(), row);
+                    if column == b'a' || fields[usize::from(pos)].is_none() {
+                        return Err("FEN marks an empty field as promoted.");
+                    }
+                    promoted |= bit(pos);
+                } else if let Some(empty) = c.to_digit(10) {
+                    column += empty as u8;
+                } else {
+                    let t = Type::from_letter(c).ok_or("FEN contains an unknown piece.")?;
+                    let side = if c.is_ascii_uppercase() {
+                        Side::White
+                    } else {
+                        Side::Black
+                    };
+                    if column > b'h' {
+                        return Err("FEN describes a row longer than 8 fields.");
+                    }
+                    let t = match t {
+                        Type::Pawn(_) => {
+                            let unmoved = variant.double_step_rows(side).contains(&row);
+                            if (row == 1 || row == 8) && !unmoved {
+                                return Err("FEN has a pawn on the first or the last row.");
+                            }
+                            Type::Pawn(!unmoved)
+                        }
+                        t => t,
+                    };
+                    fields[Pos::at(column.into(), row)] = Some(Piece::new(t, side));
+                    column += 1;
+                }
+            }
+            if column != b'h' + 1 {
+                return Err("FEN has to describe 8 fields in every row.");
+            }
+        }
+
+        let current_move = match side {
+            "w" => Side::White,
+            "b" => Side::Black,
+            _ => return Err("FEN has an invalid side to move."),
+        };
+        let mut board = Board::empty(fields, current_move);
+        board.variant = variant;
+        board.promoted = promoted;
+        for c in pocket.unwrap_or_default().chars() {
+            let t = Type::from_letter(c)
+                .filter(|&t| t != Type::King)
+                .ok_or("FEN has an invalid piece in hand.")?;
+            let side = if c.is_ascii_uppercase() {
+                Side::White
+            } else {
+                Side::Black
+            };
+            board.hand[side as usize][hand_index(t)] += 1;
+        }
+
+        variant.validate(&board)?;
+        if variant.royal_king()
+            && board
+                .king(current_move.opposite())
+                .is_some_and(|king| attacked(&board.fields, king, current_move))
+            // In Atomic chess touching kings can't capture each other and a game
+            // ends as soon as a king explodes
+            && !(variant.explosions()
+                && (board.king(current_move).is_none() || board.kings_touch(&board.fields)))
+        {
+            return Err("FEN describes a position where the side to move can capture the king.");
+        }
+
+        if castling != "-" {
+            if !variant.castling() {
+                return Err("FEN has castling rights, but the variant has no castling.");
+            }
+            for c in castling.chars() {
+                board.parse_castling(c)?;
+            }
+        }
+        board.chess960 = [Side::White, Side::Black].iter().any(|&side| {
+            let rights = board.castling[side as usize];
+            rights.iter().any(|r| r.is_some())
+                && (board.king(side).is_some_and(|king| king.column != 'e')
+                    || rights[KING_SIDE].is_some_and(|c| c != 'h')
+                    || rights[QUEEN_SIDE].is_some_and(|c| c != 'a'))
+        });
+
+        if en_passant != "-" {
+            let pos = Pos::parse(en_passant).ok_or("FEN has an invalid en passant field.")?;
+            // The field is behind a pawn of the other side which has just moved two rows
+            let expected = match current_move {
+                Side::White => 6,
+                Side::Black => 3,
+            };
+            if pos.row != expected {
+                return Err("FEN has an en passant field on a wrong row.");
+            }
+            board.en_passant = Some(pos);
+        }
+
+        board.halfmove_clock = halfmove_clock
+            .parse()
+            .map_err(|_| "FEN has an invalid half-move clock.")?;
+        board.fullmove_number = fullmove_number
+            .parse()
+            .map_err(|_| "FEN has an invalid full move number.")?;
+        if board.fullmove_number == 0 {
+            return Err("FEN has an invalid full move number.");
+        }
+
+        board.key = board.compute_key();
+        Ok(board)
+    }
+
+    /// Returns the position in Forsyth-Edwards Notation. Castling rights are written
+    /// as in X-FEN: `K` and `Q` for the outermost rooks, the column of the rook otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    ///
+    /// assert_eq!(
+    ///     Board::new().to_fen(),
+    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    /// );
+    /// ```
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        for row in (1..=8).rev() {
+            let mut empty = 0;
+            for column in 'a'..='h' {
+                match self.at(Pos::new(column, row)) {
+                    Some(p) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(match p.p_side() {
+                            Side::White => p.p_type().letter(),
+                            Side::Black => p.p_type().letter().to_ascii_lowercase(),
+                        });
+                        if self.promoted & bit(Pos::new(column, row)) != 0 {
+                            fen.push('~');
+                        }
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if row > 1 {
+                fen.push('/');
+            }
+        }
+        if self.variant.drops() {
+            fen.push('[');
+            for side in [Side::White, Side::Black] {
+                for t in IN_HAND.iter().rev() {
+                    let letter = match side {
+                        Side::White => t.letter(),
+                        Side::Black => t.letter().to_ascii_lowercase(),
+                    };
+                    for _ in 0..self.in_hand(side, *t) {
+                        fen.push(letter);
+                    }
+                }
+            }
+            fen.push(']');
+        }
+
+        fen.push_str(match self.current_move {
+            Side::White => " w ",
+            Side::Black => " b ",
+        });
+
+        let castling: String = [Side::White, Side::Black]
+            .iter()
+            .flat_map(|&side| [(side, KING_SIDE), (side, QUEEN_SIDE)])
+            .filter(|&(side, wing)| self.castling[side as usize][wing].is_some())
+            .map(|(side, wing)| {
+                let column = self.castling[side as usize][wing].expect("filtered above");
+                let c = if self.outermost_rook(side, wing) == Some(column) {
+                    if wing == KING_SIDE {
+                        'K'
+                    } else {
+                        'Q'
+                    }
+                } else {
+                    column.to_ascii_uppercase()
+                };
+                match side {
+                    Side::White => c,
+                    Side::Black => c.to_ascii_lowercase(),
+                }
+            })
+            .collect();
+        fen.push_str(if castling.is_empty() { "-" } else { &castling });
+
+        match self.en_passant {
+            Some(pos) => fen.push_str(&format!(" {pos}")),
+            None => fen.push_str(" -"),
+        }
+        fen.push_str(&format!(
+            " {} {}",
+            self.halfmove_clock, self.fullmove_number
+        ));
+        fen
+    }
+
+    /// Adds the castling right described by a single character of the FEN castling field.
+    /// `K` and `Q` refer to the outermost rook on the respective side of the king,
+    /// the letters from `A` to `H` to the rook on that column.
+    fn parse_castling(&mut self, c: char) -> Result<(), &'static str> {
+        let side = if c.is_ascii_uppercase() {
+            Side::White
+        } else {
+            Side::Black
+        };
+        let row = home_row(side);
+        let king = self
+            .king(side)
+            .filter(|king| king.row == row)
+            .ok_or("FEN has castling rights for a king outside of its home row.")?;
+
+        let (wing, column) = match c.to_ascii_uppercase() {
+            'K' => (KING_SIDE, self.outermost_rook(side, KING_SIDE)),
+            'Q' => (QUEEN_SIDE, self.outermost_rook(side, QUEEN_SIDE)),
+            'A'..='H' => {
+                let column = c.to_ascii_lowercase();
+                let wing = if column > king.column {
+                    KING_SIDE
+                } else {
+                    QUEEN_SIDE
+                };
+                let rook = Piece::new(Type::Rook, side);
+                let present = self.at(Pos::new(column, row)) == Some(&rook);
+                (wing, present.then_some(column))
+            }
+            _ => return Err("FEN has invalid castling rights."),
+        };
+        let column = column.ok_or("FEN has castling rights without a rook.")?;
+        self.castling[side as usize][wing] = Some(column);
+        Ok(())
+    }
+
+    /// Returns the column of the rook of `side` on its home row which is the farthest away
+    /// from the king on `wing`.
+    fn outermost_rook(&self, side: Side, wing: usize) -> Option<char> {
+        let row = home_row(side);
+        let king = self.king(side).filter(|king| king.row == row)?;
+        let rook = Piece::new(Type::Rook, side);
+        let mut columns: Vec<char> = if wing == KING_SIDE {
+            ('a'..='h').rev().filter(|&c| c > king.column).collect()
+        } else {
+            ('a'..='h').filter(|&c| c < king.column).collect()
+        };
+        columns.retain(|&c| self.at(Pos::new(c, row)) == Some(&rook));
+        columns.first().copied()
+    }
+}
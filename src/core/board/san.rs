@@ -0,0 +1,231 @@
+use crate::core::movement::Move;
+use crate::core::piece::{Side, Type};
+use crate::core::position::Pos;
+
+use super::{Board, Status, KING_SIDE, QUEEN_SIDE};
+
+impl Board {
+    /// Returns `movement` in standard algebraic notation (SAN), for example `Nf3`, `exd5`,
+    /// `Rae1+`, `O-O`, `e8=Q#` or the drop `N@f3`. The movement is not performed.
+    /// If the movement is illegal, an explanatory `Err` is returned, like in `make_move`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    ///
+    /// let b = Board::new();
+    ///
+    /// assert_eq!(b.san("g1f3".parse().unwrap()), Ok("Nf3".to_string()));
+    /// assert_eq!(b.san("e2e4".parse().unwrap()), Ok("e4".to_string()));
+    /// ```
+    pub fn san(&self, movement: Move) -> Result<String, &'static str> {
+        let mut after = self.clone();
+        after.make_move(movement)?;
+
+        let p = self.at(movement.start).copied();
+        let mut san = if let Some(t) = movement.drop {
+            format!("{}@{}", t.letter(), movement.end)
+        } else if let Some(wing) = p.and_then(|p| self.castling_wing(p, movement, &self.castling)) {
+            if wing == KING_SIDE {
+                "O-O".to_string()
+            } else {
+                "O-O-O".to_string()
+            }
+        } else {
+            let p = p.expect("a legal movement starts at a piece");
+            let capture = after.history.last().unwrap().captured.is_some();
+            let mut san = String::new();
+
+            if let Type::Pawn(_) = p.p_type() {
+                if capture {
+                    san.push(movement.start.column);
+                }
+            } else {
+                san.push(p.p_type().letter());
+
+                // Other pieces of the same kind which can move to the same field as well
+                let rivals: Vec<Pos> = self
+                    .legal_moves()
+                    .into_iter()
+                    .filter(|m| m.end == movement.end && m.start != movement.start)
+                    .filter(|m| self.at(m.start).map(|r| r.p_type()) == Some(p.p_type()))
+                    .map(|m| m.start)
+                    .collect();
+
+                if !rivals.is_empty() {
+                    if rivals.iter().all(|r| r.column != movement.start.column) {
+                        san.push(movement.start.column);
+                    } else if rivals.iter().all(|r| r.row != movement.start.row) {
+                        san.push((b'0' + movement.start.row).into());
+                    } else {
+                        san.push_str(&movement.start.to_string());
+                    }
+                }
+            }
+
+            if capture {
+                san.push('x');
+            }
+            san.push_str(&movement.end.to_string());
+            if let Some(t) = movement.promotion {
+                san.push('=');
+                san.push(t.letter());
+            }
+            san
+        };
+
+        if let Status::Checkmate(_) = after.status() {
+            san.push('#');
+        } else if after.in_check() {
+            san.push('+');
+        }
+        Ok(san)
+    }
+
+    /// Parses a movement written in standard algebraic notation (SAN) and returns it if it is
+    /// legal on this board. Check, mate and annotation symbols are ignored, unnecessary
+    /// disambiguation is accepted, `0-0` may be used for castling and pawn drops may be
+    /// written without the `P`, like `@e4`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    ///
+    /// let b = Board::new();
+    ///
+    /// assert_eq!(b.parse_san("Nf3"), Ok("g1f3".parse().unwrap()));
+    /// assert_eq!(b.parse_san("e4!?"), Ok("e2e4".parse().unwrap()));
+    /// assert!(b.parse_san("Nd2").is_err());
+    /// ```
+    pub fn parse_san(&self, san: &str) -> Result<Move, &'static str> {
+        let text = san.trim_end_matches(['+', '#', '!', '?']);
+        let legal = self.legal_moves();
+
+        if matches!(text, "O-O" | "O-O-O" | "0-0" | "0-0-0") {
+            let wing = if text.len() == 3 {
+                KING_SIDE
+            } else {
+                QUEEN_SIDE
+            };
+            return legal
+                .into_iter()
+                .find(|m| {
+                    self.at(m.start)
+                        .is_some_and(|&p| self.castling_wing(p, *m, &self.castling) == Some(wing))
+                })
+                .ok_or("Specified castling is impossible.");
+        }
+
+        if let Some((piece, end)) = text.split_once('@') {
+            let t = match piece {
+                "" => Type::Pawn(false),
+                piece => piece
+                    .parse::<char>()
+                    .ok()
+                    .and_then(Type::from_letter)
+                    .ok_or("Invalid piece letter.")?,
+            };
+            let end = Pos::parse(end).ok_or("Invalid end position.")?;
+            let m = Move::drop(t, end);
+            return if legal.contains(&m) {
+                Ok(m)
+            } else {
+                Err("Specified drop is impossible.")
+            };
+        }
+
+        let mut chars: Vec<char> = text.chars().collect();
+
+        // Promotion, with or without '='
+        let mut promotion = None;
+        if let Some(&last) = chars.last() {
+            if last.is_ascii_uppercase() && chars.len() > 2 {
+                promotion = Some(Type::from_letter(last).ok_or("Invalid promotion piece.")?);
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+            }
+        }
+
+        let piece = match chars.first() {
+            Some(c) if c.is_ascii_uppercase() => {
+                let t = Type::from_letter(*c).ok_or("Invalid piece letter.")?;
+                chars.remove(0);
+                t
+            }
+            _ => Type::Pawn(false),
+        };
+
+        if chars.len() < 2 {
+            return Err("Movement is too short.");
+        }
+        let end: String = chars[chars.len() - 2..].iter().collect();
+        let end = Pos::parse(&end).ok_or("Invalid end position.")?;
+        chars.truncate(chars.len() - 2);
+        if chars.last() == Some(&'x') {
+            chars.pop();
+        }
+
+        let mut from_column = None;
+        let mut from_row = None;
+        for c in chars {
+            match c {
+                'a'..='h' => from_column = Some(c),
+                '1'..='8' => from_row = Some(c as u8 - b'0'),
+                _ => return Err("Invalid character in movement."),
+            }
+        }
+
+        let mut candidates = legal.into_iter().filter(|m| {
+            let t = self.at(m.start).map(|p| p.p_type());
+            let same_type = match piece {
+                Type::Pawn(_) => matches!(t, Some(Type::Pawn(_))),
+                piece => t == Some(piece),
+            };
+            same_type
+                && m.end == end
+                && m.promotion == promotion
+                && from_column.is_none_or(|c| m.start.column == c)
+                && from_row.is_none_or(|r| m.start.row == r)
+        });
+
+        match (candidates.next(), candidates.next()) {
+            (Some(m), None) => Ok(m),
+            (None, _) => Err("Specified move is impossible."),
+            (Some(_), Some(_)) => Err("Specified move is ambiguous."),
+        }
+    }
+
+    /// Returns `moves`, performed one after another starting at this board, in standard
+    /// algebraic notation with move numbers, like `1. e4 e5 2. Nf3`.
+    /// If any of the movements is illegal, an explanatory `Err` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    ///
+    /// let mut b = Board::new();
+    /// b.make_move("e2e4".parse().unwrap()).unwrap();
+    /// let moves = ["e7e5".parse().unwrap(), "g1f3".parse().unwrap()];
+    ///
+    /// assert_eq!(b.san_line(&moves), Ok("1... e5 2. Nf3".to_string()));
+    /// ```
+    pub fn san_line(&self, moves: &[Move]) -> Result<String, &'static str> {
+        let mut board = self.clone();
+        let mut line = Vec::new();
+        for (i, &m) in moves.iter().enumerate() {
+            match board.current_move() {
+                Side::White => line.push(format!("{}.", board.fullmove_number())),
+                Side::Black if i == 0 => line.push(format!("{}...", board.fullmove_number())),
+                Side::Black => {}
+            }
+            line.push(board.san(m)?);
+            board.make_move_unchecked(m);
+        }
+        Ok(line.join(" "))
+    }
+}
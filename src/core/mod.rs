@@ -1,3 +1,8 @@
 pub mod piece;
 pub mod board;
+pub mod movement;
+pub mod pgn;
 pub mod position;
+pub mod variant;
+#[cfg(feature = "serde")]
+mod serialization;
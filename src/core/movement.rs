@@ -0,0 +1,119 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::piece::Type;
+use super::position::Pos;
+
+/// A movement of the piece at `start` to `end`.
+/// `promotion` holds the type a pawn becomes when it reaches the last row.
+/// Castling is written as the movement of the king, like `e1g1`.
+/// In variants like Crazyhouse `drop` holds the type of a piece put from the hand
+/// onto the empty field `end`, then `start` is the same as `end`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Move {
+    pub start: Pos,
+    pub end: Pos,
+    pub promotion: Option<Type>,
+    pub drop: Option<Type>,
+}
+
+impl Move {
+    /// Constructs a movement without a promotion
+    pub fn new(start: Pos, end: Pos) -> Self {
+        Self {
+            start,
+            end,
+            promotion: None,
+            drop: None,
+        }
+    }
+
+    /// Constructs a movement of a pawn which is promoted to `promotion`
+    pub fn with_promotion(start: Pos, end: Pos, promotion: Type) -> Self {
+        Self {
+            start,
+            end,
+            promotion: Some(promotion),
+            drop: None,
+        }
+    }
+
+    /// Constructs a drop of a piece of type `t` from the hand onto `end`
+    pub fn drop(t: Type, end: Pos) -> Self {
+        Self {
+            start: end,
+            end,
+            promotion: None,
+            drop: Some(t),
+        }
+    }
+}
+
+/// Writes the movement in the long algebraic notation used by UCI, like `e2e4` or `e7e8q`,
+/// and drops like `N@f3`.
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(t) = self.drop {
+            return write!(f, "{}@{}", t.letter(), self.end);
+        }
+        write!(f, "{}{}", self.start, self.end)?;
+        if let Some(t) = self.promotion {
+            write!(f, "{}", t.letter().to_ascii_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the long algebraic notation used by UCI, like `e2e4` or `e7e8q`,
+/// and drops like `N@f3`. Whether the variant allows the promotion is left to
+/// `Board::make_move`.
+///
+/// # Example
+///
+/// ```
+/// use rust_chess::core::movement::Move;
+/// use rust_chess::core::piece::Type;
+/// use rust_chess::core::position::Pos;
+///
+/// let m: Move = "e7e8q".parse().unwrap();
+/// assert_eq!(m, Move::with_promotion(Pos::new('e', 7), Pos::new('e', 8), Type::Queen));
+/// assert_eq!(m.to_string(), "e7e8q");
+///
+/// assert!("e7e9".parse::<Move>().is_err());
+///
+/// let drop: Move = "N@f3".parse().unwrap();
+/// assert_eq!(drop, Move::drop(Type::Knight, Pos::new('f', 3)));
+///
+/// // Pawns may become kings in Antichess
+/// let king: Move = "e7e8k".parse().unwrap();
+/// assert_eq!(king.promotion, Some(Type::King));
+/// assert_eq!(king.to_string(), "e7e8k");
+/// ```
+impl FromStr for Move {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.is_ascii() || !(4..=5).contains(&s.len()) {
+            return Err("A movement must look like 'e2e4' or 'e7e8q'.");
+        }
+        if s.len() == 4 && &s[1..2] == "@" {
+            let end = Pos::parse(&s[2..4]).ok_or("Invalid end position.")?;
+            return match s.chars().next().and_then(Type::from_letter) {
+                Some(Type::King) | None => Err("Invalid piece to drop."),
+                Some(t) => Ok(Move::drop(t, end)),
+            };
+        }
+        let start = Pos::parse(&s[0..2]).ok_or("Invalid start position.")?;
+        let end = Pos::parse(&s[2..4]).ok_or("Invalid end position.")?;
+
+        match s[4..].chars().next() {
+            None => Ok(Move::new(start, end)),
+            Some(c) => match Type::from_letter(c) {
+                Some(t @ (Type::Queen | Type::Rook | Type::Bishop | Type::Knight | Type::King)) => {
+                    Ok(Move::with_promotion(start, end, t))
+                }
+                _ => Err("Invalid promotion piece."),
+            },
+        }
+    }
+}
@@ -0,0 +1,361 @@
+//! Reading and writing games in Portable Game Notation (PGN).
+
+use std::error::Error;
+use std::fmt;
+
+use super::board::Board;
+use super::movement::Move;
+use super::piece::Side;
+use super::variant::{self, Variant};
+
+/// Maximal length of the lines of the movement text written by `PgnGame`
+const LINE_LENGTH: usize = 79;
+
+/// A game read from PGN: its tag pairs, the movements of the main line in standard
+/// algebraic notation and the result from the end of the movement text.
+/// Comments, variations and annotation glyphs are skipped.
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<String>,
+    pub result: String,
+    /// Line of the PGN text where the game starts
+    pub line: usize,
+}
+
+/// Error found while reading or replaying a PGN game
+#[derive(Debug, PartialEq)]
+pub struct PgnError {
+    /// Line of the PGN text where the error was found
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl Error for PgnError {}
+
+impl PgnGame {
+    /// Constructs a game from the movements performed on `board`. The tags of the
+    /// Seven Tag Roster are added with unknown values, except for the result, and the
+    /// starting position is stored in a `FEN` tag if it isn't the one of the variant,
+    /// which is named in the `Variant` tag unless it is standard chess.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    /// use rust_chess::core::pgn::PgnGame;
+    ///
+    /// let mut b = Board::new();
+    /// for m in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+    ///     b.make_move(m.parse().unwrap()).unwrap();
+    /// }
+    /// let game = PgnGame::from_board(&b);
+    ///
+    /// assert_eq!(game.tag("Result"), Some("0-1"));
+    /// assert!(game.to_string().ends_with("\n\n1. f3 e5 2. g4 Qh4# 0-1\n"));
+    /// ```
+    pub fn from_board(board: &Board) -> PgnGame {
+        let start = board.start_position();
+        let result = board.status().result().to_string();
+
+        let mut replay = start.clone();
+        let mut moves = Vec::new();
+        for m in board.moves_played() {
+            moves.push(replay.san(m).expect("performed movements are legal"));
+            replay.make_move_unchecked(m);
+        }
+
+        let mut game = PgnGame {
+            tags: Vec::new(),
+            moves,
+            result: result.clone(),
+            line: 1,
+        };
+        for (name, value) in [
+            ("Event", "?"),
+            ("Site", "?"),
+            ("Date", "????.??.??"),
+            ("Round", "?"),
+            ("White", "?"),
+            ("Black", "?"),
+            ("Result", &result),
+        ] {
+            game.set_tag(name, value);
+        }
+        let variant = board.variant();
+        if variant.name() != variant::Standard.name() {
+            game.set_tag("Variant", variant.name());
+        } else if board.is_chess960() {
+            game.set_tag("Variant", "Chess960");
+        }
+        let fen = start.to_fen();
+        if fen != variant.start_fen() {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &fen);
+        }
+        game
+    }
+
+    /// Sets the value of the tag `name`, adding the tag if it isn't present.
+    /// Setting the `Result` tag also changes the result at the end of the movement text.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        if name == "Result" {
+            self.result = value.to_string();
+        }
+        match self.tags.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Returns the value of the tag `name`, if present.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the board the game starts from: the position of the `FEN` tag
+    /// or the starting position, played by the rules named in the `Variant` tag.
+    /// Unknown variants are played as standard chess. Castling is written like
+    /// in Chess960 if the `Variant` tag is `Chess960`.
+    pub fn start(&self) -> Result<Board, PgnError> {
+        let variant = self
+            .tag("Variant")
+            .and_then(variant::by_name)
+            .unwrap_or(&variant::Standard);
+        let mut board = match self.tag("FEN") {
+            Some(fen) => Board::from_fen_variant(fen, variant).map_err(|reason| PgnError {
+                line: self.line,
+                reason: format!("invalid FEN tag: {reason}"),
+            })?,
+            None => Board::new_variant(variant),
+        };
+        if self
+            .tag("Variant")
+            .is_some_and(|v| v.eq_ignore_ascii_case("chess960"))
+        {
+            board.set_chess960(true);
+        }
+        Ok(board)
+    }
+
+    /// Performs all movements of the game, verifying that each of them is legal,
+    /// and returns the board after the last one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::pgn;
+    ///
+    /// let games = pgn::parse("1. f3 e5 2. g4 {blunder} Qh4# 0-1").unwrap();
+    /// let board = games[0].replay().unwrap();
+    ///
+    /// assert!(board.status().is_over());
+    ///
+    /// let games = pgn::parse("1. e4 e5 2. Ke3 *").unwrap();
+    /// assert!(games[0].replay().is_err());
+    /// ```
+    pub fn replay(&self) -> Result<Board, PgnError> {
+        let mut board = self.start()?;
+        for (i, san) in self.moves.iter().enumerate() {
+            let m: Move = board.parse_san(san).map_err(|reason| PgnError {
+                line: self.line,
+                reason: format!("movement {} '{san}': {reason}", i + 1),
+            })?;
+            board.make_move(m).map_err(|reason| PgnError {
+                line: self.line,
+                reason: reason.to_string(),
+            })?;
+        }
+        Ok(board)
+    }
+}
+
+/// Writes the game in the PGN export format: the tags, an empty line and the movement text
+/// with move numbers, split into lines of at most 79 characters.
+impl fmt::Display for PgnGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in &self.tags {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(f, "[{name} \"{value}\"]")?;
+        }
+        if !self.tags.is_empty() {
+            writeln!(f)?;
+        }
+
+        let (mut side, mut number) = match self.start() {
+            Ok(board) => (board.current_move(), board.fullmove_number()),
+            Err(_) => (Side::White, 1),
+        };
+        let mut tokens = Vec::new();
+        for (i, san) in self.moves.iter().enumerate() {
+            match side {
+                Side::White => tokens.push(format!("{number}.")),
+                Side::Black if i == 0 => tokens.push(format!("{number}...")),
+                Side::Black => {}
+            }
+            if side == Side::Black {
+                number += 1;
+            }
+            side = side.opposite();
+            tokens.push(san.clone());
+        }
+        tokens.push(self.result.clone());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_LENGTH {
+                writeln!(f, "{line}")?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        writeln!(f, "{line}")
+    }
+}
+
+/// Reads all games from a PGN text. Only the syntax is checked,
+/// the movements can be verified with `PgnGame::replay`.
+pub fn parse(text: &str) -> Result<Vec<PgnGame>, PgnError> {
+    let mut games = Vec::new();
+    let mut game = PgnGame::default();
+    let mut in_movetext = false;
+    let mut comment = false;
+    let mut variations = 0;
+
+    for (number, line) in text.lines().enumerate() {
+        let number = number + 1;
+        let error = |reason: &str| PgnError {
+            line: number,
+            reason: reason.to_string(),
+        };
+
+        // Lines starting with '%' are escaped and ignored
+        if line.starts_with('%') {
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if !comment && variations == 0 && trimmed.starts_with('[') {
+            if in_movetext {
+                return Err(error("tag pair inside of the movement text"));
+            }
+            if game.tags.is_empty() {
+                game.line = number;
+            }
+            game.tags
+                .push(parse_tag(trimmed).ok_or_else(|| error("invalid tag pair"))?);
+            continue;
+        }
+
+        let mut chars = trimmed.chars().peekable();
+        let mut token = String::new();
+        loop {
+            let c = chars.next();
+            if comment {
+                if c == Some('}') {
+                    comment = false;
+                }
+                if c.is_none() {
+                    break;
+                }
+                continue;
+            }
+
+            let separator = match c {
+                None => true,
+                Some(c) => c.is_whitespace() || "{}();".contains(c),
+            };
+            if separator && !token.is_empty() {
+                if variations == 0 {
+                    if game.moves.is_empty() && game.tags.is_empty() && !in_movetext {
+                        game.line = number;
+                    }
+                    in_movetext = true;
+                    if matches!(token.as_str(), "1-0" | "0-1" | "1/2-1/2" | "*") {
+                        game.result = token.clone();
+                        games.push(std::mem::take(&mut game));
+                        in_movetext = false;
+                    } else if let Some(san) = movement(&token) {
+                        game.moves.push(san.to_string());
+                    }
+                }
+                token.clear();
+            }
+
+            match c {
+                None => break,
+                Some('{') => comment = true,
+                Some('(') => variations += 1,
+                Some(')') if variations == 0 => return Err(error("unbalanced ')'")),
+                Some(')') => variations -= 1,
+                // The rest of the line is a comment
+                Some(';') => break,
+                Some(c) if !c.is_whitespace() && c != '}' => token.push(c),
+                _ => {}
+            }
+        }
+    }
+
+    if comment {
+        return Err(PgnError {
+            line: text.lines().count(),
+            reason: "unterminated comment".to_string(),
+        });
+    }
+    if variations > 0 {
+        return Err(PgnError {
+            line: text.lines().count(),
+            reason: "unterminated variation".to_string(),
+        });
+    }
+    if in_movetext || !game.tags.is_empty() {
+        return Err(PgnError {
+            line: game.line,
+            reason: "game without a result at its end".to_string(),
+        });
+    }
+    Ok(games)
+}
+
+/// Parses a tag pair like `[Event "Casual game"]`.
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?.trim();
+    let (name, value) = inner.split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((
+        name.to_string(),
+        value.replace("\\\"", "\"").replace("\\\\", "\\"),
+    ))
+}
+
+/// Returns the movement contained in a token of the movement text, without the move number,
+/// or `None` if the token is only a move number or an annotation glyph like `$1`.
+fn movement(token: &str) -> Option<&str> {
+    if token.starts_with('$') {
+        return None;
+    }
+    let san = match token.split_once('.') {
+        Some((number, rest)) if number.chars().all(|c| c.is_ascii_digit()) => {
+            rest.trim_start_matches('.')
+        }
+        _ => token,
+    };
+    if san.is_empty() {
+        None
+    } else {
+        Some(san)
+    }
+}
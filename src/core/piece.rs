@@ -1,13 +1,15 @@
 use std::fmt;
 
-#[derive(Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Used to store a chess piece and its data
 pub struct Piece {
     p_type: Type,
     p_side: Side,
 }
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Type of a chess piece.
 /// Pawns also hold a boolean indicating whether it was moved.
 pub enum Type {
@@ -19,13 +21,24 @@ pub enum Type {
     Pawn(bool),
 }
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Side the chess piece is on
 pub enum Side {
     White,
     Black,
 }
 
+impl Side {
+    /// Returns the other side
+    pub fn opposite(&self) -> Side {
+        match self {
+            Side::White => Side::Black,
+            Side::Black => Side::White,
+        }
+    }
+}
+
 impl fmt::Display for Side {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -35,6 +48,48 @@ impl fmt::Display for Side {
     }
 }
 
+impl Type {
+    /// Returns the uppercase English letter of the piece type as used in
+    /// algebraic notation and FEN (`'K'`, `'Q'`, `'R'`, `'B'`, `'N'` or `'P'`).
+    pub fn letter(&self) -> char {
+        match self {
+            Type::King => 'K',
+            Type::Queen => 'Q',
+            Type::Rook => 'R',
+            Type::Bishop => 'B',
+            Type::Knight => 'N',
+            Type::Pawn(_) => 'P',
+        }
+    }
+
+    /// Parses an English piece letter as used in algebraic notation and FEN.
+    /// Both cases are accepted. A parsed pawn is marked as not moved.
+    pub fn from_letter(c: char) -> Option<Type> {
+        match c.to_ascii_uppercase() {
+            'K' => Some(Type::King),
+            'Q' => Some(Type::Queen),
+            'R' => Some(Type::Rook),
+            'B' => Some(Type::Bishop),
+            'N' => Some(Type::Knight),
+            'P' => Some(Type::Pawn(false)),
+            _ => None,
+        }
+    }
+
+    /// Returns the conventional material value of the piece type in centipawns.
+    /// The king has no material value and returns 0.
+    pub fn value(&self) -> i32 {
+        match self {
+            Type::King => 0,
+            Type::Queen => 900,
+            Type::Rook => 500,
+            Type::Bishop => 300,
+            Type::Knight => 300,
+            Type::Pawn(_) => 100,
+        }
+    }
+}
+
 impl Piece {
     /// Construct a piece from a given side and type
     pub fn new(p_type: Type, p_side: Side) -> Self {
@@ -55,31 +110,3 @@ impl Piece {
         }
     }
 }
-
-impl fmt::Display for Type {
-    #[cfg(feature = "ascii")]
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let c = match self {
-            Type::King => 'K',
-            Type::Queen => 'Q',
-            Type::Rook => 'R',
-            Type::Bishop => 'B',
-            Type::Knight => 'k',
-            Type::Pawn => 'p',
-        };
-        write!(f, "{c}")
-    }
-
-    #[cfg(feature = "unicode")]
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let c = match self {
-            Type::King => '♚',
-            Type::Queen => '♛',
-            Type::Rook => '♜',
-            Type::Bishop => '♝',
-            Type::Knight => '♞',
-            Type::Pawn(_) => '♟',
-        };
-        write!(f, "{c}")
-    }
-}
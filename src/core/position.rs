@@ -1,4 +1,6 @@
-#[derive(Debug, PartialEq, Clone, Copy)]
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct Pos {
     pub column: char,
     pub row: u8,
@@ -31,13 +33,47 @@ impl Pos {
     /// assert_eq!(p.get(), Pos::at('d', 4));
     /// ```
     pub fn at(column: char, row: u8) -> usize {
-        (column as u8 - 'a' as u8 + (row - 1) * 8).into()
+        (column as u8 - b'a' + (row - 1) * 8).into()
     }
     /// Returns an index to a 64 element slice at the position stored in `&self`.
     /// If the position is ill-formed this method might or might not panic.
     pub fn get(&self) -> usize {
-        (self.column as u8 - 'a' as u8 + (self.row - 1) * 8).into()
+        (self.column as u8 - b'a' + (self.row - 1) * 8).into()
     }
+    /// Returns the position moved by `columns` to the right and `rows` up (from White's
+    /// perspective), or `None` if it lies outside of the board.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::position::Pos;
+    ///
+    /// assert_eq!(Pos::new('b', 1).offset(-1, 2), Some(Pos::new('a', 3)));
+    /// assert_eq!(Pos::new('b', 1).offset(-2, 1), None);
+    /// ```
+    pub fn offset(&self, columns: i8, rows: i8) -> Option<Pos> {
+        let column = self.column as i8 + columns;
+        let row = self.row as i8 + rows;
+        let pos = Pos::new((column as u8).into(), row as u8);
+        if (b'a' as i8..=b'h' as i8).contains(&column) && (1..=8).contains(&row) {
+            Some(pos)
+        } else {
+            None
+        }
+    }
+
+    /// Parses a position written as a column letter followed by a row number, like `e4`.
+    /// Returns `None` if `s` is not a valid position.
+    pub fn parse(s: &str) -> Option<Pos> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(column @ 'a'..='h'), Some(row @ '1'..='8'), None) => {
+                Some(Pos::new(column, row as u8 - b'0'))
+            }
+            _ => None,
+        }
+    }
+
     /// Returns true if the position is a valid position in context of a standard chess board.
     /// If the position is ill-formed this method might return false, but it is not guaranteed
     /// for all situations.
@@ -64,6 +100,12 @@ impl Pos {
 /// Can be used interchangeably with `Pos::get()`
 impl From<Pos> for usize {
     fn from(value: Pos) -> Self {
-        (value.column as u8 - 'a' as u8 + (value.row - 1) * 8).into()
+        (value.column as u8 - b'a' + (value.row - 1) * 8).into()
+    }
+}
+
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.column, self.row)
     }
 }
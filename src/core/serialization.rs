@@ -0,0 +1,137 @@
+//! `Serialize` and `Deserialize` for the types of `core`, built with the `serde`
+//! feature. Pieces, their types and sides and PGN games are derived, positions are
+//! written like `e4` and movements in UCI notation like `e7e8q`. A board is written
+//! as its variant, its starting position and the movements performed since, so taking
+//! back movements and counting repetitions keep working after it is read again.
+//!
+//! # Example
+//!
+//! ```
+//! use rust_chess::core::board::Board;
+//! use rust_chess::core::movement::Move;
+//! use rust_chess::core::piece::{Piece, Side, Type};
+//! use rust_chess::core::position::Pos;
+//!
+//! let m: Move = "e7e8q".parse().unwrap();
+//! assert_eq!(serde_json::to_string(&m).unwrap(), r#""e7e8q""#);
+//! assert_eq!(serde_json::from_str::<Move>(r#""N@f3""#).unwrap(), "N@f3".parse().unwrap());
+//! assert_eq!(serde_json::to_string(&Pos::new('e', 4)).unwrap(), r#""e4""#);
+//! assert!(serde_json::from_str::<Pos>(r#""i9""#).is_err());
+//!
+//! let piece = Piece::new(Type::Pawn(true), Side::Black);
+//! let json = serde_json::to_string(&piece).unwrap();
+//! assert_eq!(serde_json::from_str::<Piece>(&json).unwrap(), piece);
+//!
+//! let mut b = Board::new();
+//! for m in ["e2e4", "e7e5", "g1f3"] {
+//!     b.make_move(m.parse().unwrap()).unwrap();
+//! }
+//! let json = serde_json::to_string(&b).unwrap();
+//! assert_eq!(
+//!     json,
+//!     r#"{"variant":"Standard","chess960":false,"start":"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1","moves":["e2e4","e7e5","g1f3"]}"#
+//! );
+//! let mut read: Board = serde_json::from_str(&json).unwrap();
+//! assert_eq!(read.to_fen(), b.to_fen());
+//! assert_eq!(read.unmake_move(), Some("g1f3".parse().unwrap()));
+//!
+//! let illegal = json.replace("g1f3", "g1g3");
+//! assert!(serde_json::from_str::<Board>(&illegal).is_err());
+//! ```
+//!
+//! Variants and games read from PGN are kept as well:
+//!
+//! ```
+//! use rust_chess::core::board::Board;
+//! use rust_chess::core::pgn::{self, PgnGame};
+//! use rust_chess::core::variant;
+//!
+//! let mut b = Board::new_variant(variant::by_name("crazyhouse").unwrap());
+//! for m in ["e2e4", "d7d5", "e4d5", "d8d5", "P@e4"] {
+//!     b.make_move(m.parse().unwrap()).unwrap();
+//! }
+//! let read: Board = serde_json::from_str(&serde_json::to_string(&b).unwrap()).unwrap();
+//! assert_eq!(read.to_fen(), b.to_fen());
+//! assert_eq!(read.variant().name(), "Crazyhouse");
+//!
+//! let game = &pgn::parse("[White \"Morphy\"]\n\n1. e4 e5 2. Nf3 d6 1-0\n").unwrap()[0];
+//! let json = serde_json::to_string(game).unwrap();
+//! assert_eq!(&serde_json::from_str::<PgnGame>(&json).unwrap(), game);
+//! ```
+
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+use super::board::Board;
+use super::movement::Move;
+use super::position::Pos;
+use super::variant;
+
+impl Serialize for Pos {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Pos {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Pos::parse(&s).ok_or_else(|| de::Error::custom(format!("invalid position '{s}'")))
+    }
+}
+
+impl Serialize for Move {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Move {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// How a board is written
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Board")]
+struct Record {
+    /// Name of the variant as written in the PGN `Variant` tag
+    variant: String,
+    chess960: bool,
+    /// FEN of the starting position
+    start: String,
+    moves: Vec<Move>,
+}
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Record {
+            variant: self.variant().name().to_string(),
+            chess960: self.is_chess960(),
+            start: self.start_position().to_fen(),
+            moves: self.moves_played(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Fails if the variant is unknown, the starting position is invalid or a movement
+/// is illegal.
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let record = Record::deserialize(deserializer)?;
+        let rules = variant::by_name(&record.variant)
+            .ok_or_else(|| de::Error::custom(format!("unknown variant '{}'", record.variant)))?;
+        let mut board = Board::from_fen_variant(&record.start, rules).map_err(de::Error::custom)?;
+        board.set_chess960(record.chess960);
+        for m in record.moves {
+            board
+                .make_move(m)
+                .map_err(|e| de::Error::custom(format!("movement {m}: {e}")))?;
+        }
+        Ok(board)
+    }
+}
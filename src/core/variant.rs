@@ -0,0 +1,173 @@
+//! Rule sets besides standard chess. A `Board` consults its variant wherever the rules
+//! differ, so the movement generation, the notation, the engine and the interfaces work
+//! with every variant.
+
+use super::board::{Board, Status, START_FEN};
+use super::movement::Move;
+use super::piece::{Side, Type};
+
+mod antichess;
+mod atomic;
+mod crazyhouse;
+mod horde;
+mod king_of_the_hill;
+mod three_check;
+
+pub use antichess::Antichess;
+pub use atomic::Atomic;
+pub use crazyhouse::Crazyhouse;
+pub use horde::Horde;
+pub use king_of_the_hill::KingOfTheHill;
+pub use three_check::ThreeCheck;
+
+/// Rules of a chess variant. Every method defaults to the rules of standard chess,
+/// so a variant only overrides what it changes.
+pub trait Variant: Sync {
+    /// Returns the name as written in the PGN `Variant` tag, like `King of the Hill`.
+    fn name(&self) -> &'static str;
+
+    /// Returns the starting position in Forsyth-Edwards Notation.
+    fn start_fen(&self) -> &'static str {
+        START_FEN
+    }
+
+    /// Returns true if the king may not be left in check and can't be captured.
+    fn royal_king(&self) -> bool {
+        true
+    }
+
+    /// Returns true if the king may castle.
+    fn castling(&self) -> bool {
+        true
+    }
+
+    /// Returns the types a pawn can be promoted to.
+    fn promotions(&self) -> &'static [Type] {
+        &[Type::Queen, Type::Rook, Type::Bishop, Type::Knight]
+    }
+
+    /// Returns the rows from which the pawns of `side` may move two fields.
+    /// Pawns may only stand on the first or the last row if it is one of them.
+    fn double_step_rows(&self, side: Side) -> &'static [u8] {
+        match side {
+            Side::White => &[2],
+            Side::Black => &[7],
+        }
+    }
+
+    /// Returns true if captured pieces go to the hand of the capturing side, from where
+    /// they can be dropped onto empty fields.
+    fn drops(&self) -> bool {
+        false
+    }
+
+    /// Returns true if every capture explodes the capturing piece and all pieces but pawns
+    /// around the captured one. A movement may not explode the own king.
+    fn explosions(&self) -> bool {
+        false
+    }
+
+    /// Returns true if the board should count the checks given by each side,
+    /// see `Board::checks_given`.
+    fn counts_checks(&self) -> bool {
+        false
+    }
+
+    /// Returns true if the side to move has to capture whenever it can.
+    fn compulsory_captures(&self) -> bool {
+        false
+    }
+
+    /// Returns the value of a piece of type `t` in centipawns, as the engine weighs it.
+    /// Negative if a side would rather lose its pieces.
+    fn piece_value(&self, t: Type) -> i32 {
+        t.value()
+    }
+
+    /// Returns true if a position where neither side can checkmate is a draw.
+    fn draws_on_insufficient_material(&self) -> bool {
+        true
+    }
+
+    /// Verifies the pieces of a position set up from a FEN. Standard chess requires
+    /// exactly one king of each side.
+    fn validate(&self, board: &Board) -> Result<(), &'static str> {
+        for side in [Side::White, Side::Black] {
+            if count(board, side, Type::King) != 1 {
+                return Err("FEN has to contain exactly one king of each side.");
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the movements of the side to move which the variant forbids from `moves`,
+    /// the movements allowed by the standard rules.
+    fn restrict(&self, _board: &Board, moves: Vec<Move>) -> Vec<Move> {
+        moves
+    }
+
+    /// Returns the state of the game if the variant ends it regardless of the legal
+    /// movements, like a king reaching the centre. Checked before all other rules.
+    fn outcome(&self, _board: &Board) -> Option<Status> {
+        None
+    }
+
+    /// Returns the state of the game when the side to move has no legal movements:
+    /// checkmate if it is in check, else stalemate.
+    fn no_moves(&self, board: &Board) -> Status {
+        if board.in_check() {
+            Status::Checkmate(board.current_move().opposite())
+        } else {
+            Status::Stalemate
+        }
+    }
+}
+
+/// Standard chess
+pub struct Standard;
+
+impl Variant for Standard {
+    fn name(&self) -> &'static str {
+        "Standard"
+    }
+}
+
+/// All variants, standard chess first
+pub const VARIANTS: [&dyn Variant; 7] = [
+    &Standard,
+    &KingOfTheHill,
+    &ThreeCheck,
+    &Antichess,
+    &Horde,
+    &Crazyhouse,
+    &Atomic,
+];
+
+/// Returns the variant called `name`, ignoring case, spaces and hyphens,
+/// so `King of the Hill` may also be written as `kingofthehill`.
+///
+/// # Example
+///
+/// ```
+/// use rust_chess::core::variant;
+///
+/// assert_eq!(variant::by_name("three-check").unwrap().name(), "Three-check");
+/// assert!(variant::by_name("shogi").is_none());
+/// ```
+pub fn by_name(name: &str) -> Option<&'static dyn Variant> {
+    let simplify = |s: &str| -> String {
+        s.chars()
+            .filter(|c| !matches!(c, ' ' | '-'))
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    };
+    let name = simplify(name);
+    VARIANTS
+        .into_iter()
+        .find(|variant| simplify(variant.name()) == name)
+}
+
+/// Returns the number of pieces of `side` with the type `t` on `board`.
+fn count(board: &Board, side: Side, t: Type) -> usize {
+    board.pieces(side).filter(|(_, p)| p.p_type() == t).count()
+}
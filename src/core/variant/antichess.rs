@@ -0,0 +1,66 @@
+use crate::core::board::{Board, Status};
+use crate::core::movement::Move;
+use crate::core::piece::Type;
+
+use super::Variant;
+
+/// Antichess: capturing is compulsory and a side wins by losing all its pieces or by
+/// having no legal movements. The king is an ordinary piece which can be captured,
+/// pawns may also be promoted to kings and there is no castling.
+pub struct Antichess;
+
+impl Variant for Antichess {
+    fn name(&self) -> &'static str {
+        "Antichess"
+    }
+
+    fn start_fen(&self) -> &'static str {
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1"
+    }
+
+    fn royal_king(&self) -> bool {
+        false
+    }
+
+    fn castling(&self) -> bool {
+        false
+    }
+
+    fn promotions(&self) -> &'static [Type] {
+        &[
+            Type::Queen,
+            Type::Rook,
+            Type::Bishop,
+            Type::Knight,
+            Type::King,
+        ]
+    }
+
+    fn compulsory_captures(&self) -> bool {
+        true
+    }
+
+    fn piece_value(&self, t: Type) -> i32 {
+        -t.value()
+    }
+
+    fn draws_on_insufficient_material(&self) -> bool {
+        false
+    }
+
+    fn validate(&self, _board: &Board) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn restrict(&self, board: &Board, moves: Vec<Move>) -> Vec<Move> {
+        if moves.iter().any(|&m| board.is_capture(m)) {
+            moves.into_iter().filter(|&m| board.is_capture(m)).collect()
+        } else {
+            moves
+        }
+    }
+
+    fn no_moves(&self, board: &Board) -> Status {
+        Status::VariantWin(board.current_move(), "no pieces or movements left")
+    }
+}
@@ -0,0 +1,38 @@
+use crate::core::board::{Board, Status};
+use crate::core::piece::{Side, Type};
+
+use super::{count, Variant};
+
+/// Atomic chess: a capture explodes the capturing piece and all pieces but pawns around
+/// the captured one. A side wins by exploding the other king, so kings can't capture
+/// and kings standing next to each other can't be checked.
+pub struct Atomic;
+
+impl Variant for Atomic {
+    fn name(&self) -> &'static str {
+        "Atomic"
+    }
+
+    fn explosions(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, board: &Board) -> Result<(), &'static str> {
+        // A king is missing once it has exploded
+        if [Side::White, Side::Black]
+            .into_iter()
+            .any(|side| count(board, side, Type::King) > 1)
+        {
+            return Err("FEN can't contain more than one king of each side.");
+        }
+        Ok(())
+    }
+
+    fn outcome(&self, board: &Board) -> Option<Status> {
+        let side = board.current_move();
+        board
+            .king(side)
+            .is_none()
+            .then_some(Status::VariantWin(side.opposite(), "king exploded"))
+    }
+}
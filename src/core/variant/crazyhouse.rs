@@ -0,0 +1,24 @@
+use super::Variant;
+
+/// Crazyhouse: captured pieces go to the hand of the capturing side and may be dropped
+/// onto any empty field instead of moving, pawns not on the first or the last row.
+/// Promoted pieces become pawns again when they are captured.
+pub struct Crazyhouse;
+
+impl Variant for Crazyhouse {
+    fn name(&self) -> &'static str {
+        "Crazyhouse"
+    }
+
+    fn start_fen(&self) -> &'static str {
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1"
+    }
+
+    fn drops(&self) -> bool {
+        true
+    }
+
+    fn draws_on_insufficient_material(&self) -> bool {
+        false
+    }
+}
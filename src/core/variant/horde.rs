@@ -0,0 +1,43 @@
+use crate::core::board::{Board, Status};
+use crate::core::piece::{Side, Type};
+
+use super::{count, Variant};
+
+/// Horde: White has 36 pawns and no king, Black the usual pieces. Black wins by
+/// capturing all White pieces, White by checkmating. White pawns on the first row may
+/// move two fields like those on the second.
+pub struct Horde;
+
+impl Variant for Horde {
+    fn name(&self) -> &'static str {
+        "Horde"
+    }
+
+    fn start_fen(&self) -> &'static str {
+        "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1"
+    }
+
+    fn double_step_rows(&self, side: Side) -> &'static [u8] {
+        match side {
+            Side::White => &[1, 2],
+            Side::Black => &[7],
+        }
+    }
+
+    fn draws_on_insufficient_material(&self) -> bool {
+        false
+    }
+
+    fn validate(&self, board: &Board) -> Result<(), &'static str> {
+        if count(board, Side::White, Type::King) != 0 || count(board, Side::Black, Type::King) != 1
+        {
+            return Err("FEN has to contain no White king and exactly one Black king.");
+        }
+        Ok(())
+    }
+
+    fn outcome(&self, board: &Board) -> Option<Status> {
+        (board.pieces(Side::White).next().is_none())
+            .then_some(Status::VariantWin(Side::Black, "all White pieces captured"))
+    }
+}
@@ -0,0 +1,27 @@
+use crate::core::board::{Board, Status};
+use crate::core::piece::Side;
+
+use super::Variant;
+
+/// King of the Hill: a side also wins by bringing its king to one of the four fields
+/// in the centre, d4, e4, d5 and e5.
+pub struct KingOfTheHill;
+
+impl Variant for KingOfTheHill {
+    fn name(&self) -> &'static str {
+        "King of the Hill"
+    }
+
+    fn draws_on_insufficient_material(&self) -> bool {
+        false
+    }
+
+    fn outcome(&self, board: &Board) -> Option<Status> {
+        [Side::White, Side::Black].into_iter().find_map(|side| {
+            board
+                .king(side)
+                .filter(|king| matches!(king.column, 'd' | 'e') && matches!(king.row, 4 | 5))
+                .map(|_| Status::VariantWin(side, "king reached the centre"))
+        })
+    }
+}
@@ -0,0 +1,28 @@
+use crate::core::board::{Board, Status};
+use crate::core::piece::Side;
+
+use super::Variant;
+
+/// Three-check: a side also wins by giving check for the third time.
+pub struct ThreeCheck;
+
+impl Variant for ThreeCheck {
+    fn name(&self) -> &'static str {
+        "Three-check"
+    }
+
+    fn counts_checks(&self) -> bool {
+        true
+    }
+
+    fn draws_on_insufficient_material(&self) -> bool {
+        false
+    }
+
+    fn outcome(&self, board: &Board) -> Option<Status> {
+        [Side::White, Side::Black]
+            .into_iter()
+            .find(|&side| board.checks_given(side) >= 3)
+            .map(|side| Status::VariantWin(side, "third check"))
+    }
+}
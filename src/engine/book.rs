@@ -0,0 +1,350 @@
+//! Opening books in the Polyglot format. A book is a sorted list of entries of 16 bytes,
+//! all numbers big-endian: the key of the position as computed by
+//! `Board::polyglot_key`, the movement, its weight and 4 bytes of learning data.
+//! Books are read with `Book` and built from games with `BookBuilder`.
+
+use std::cmp::Reverse;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::BuildHasher;
+use std::io;
+use std::path::Path;
+
+use crate::core::board::Board;
+use crate::core::movement::Move;
+use crate::core::pgn::{PgnError, PgnGame};
+use crate::core::piece::{Side, Type};
+use crate::core::position::Pos;
+use crate::core::variant::{self, Variant};
+
+/// Size of an entry in bytes
+const ENTRY_SIZE: usize = 16;
+
+/// A movement found in a book for a position
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Entry {
+    pub movement: Move,
+    /// How often the movement should be played compared to the others
+    pub weight: u16,
+    /// Learning data, unused by this program
+    pub learn: u32,
+}
+
+/// An entry as stored in the file
+#[derive(Debug, Clone, Copy)]
+struct Record {
+    key: u64,
+    movement: u16,
+    weight: u16,
+    learn: u32,
+}
+
+/// A Polyglot opening book
+#[derive(Debug, Default, Clone)]
+pub struct Book {
+    /// Sorted by key
+    records: Vec<Record>,
+}
+
+impl Book {
+    /// Reads a book from the contents of a `.bin` file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    /// use rust_chess::engine::book::Book;
+    ///
+    /// // The starting position with 1. e4 weighted 3 and 1. d4 weighted 1
+    /// let mut bytes = Vec::new();
+    /// for (movement, weight) in [(0x031c_u16, 3_u16), (0x02db, 1)] {
+    ///     bytes.extend(Board::new().polyglot_key().to_be_bytes());
+    ///     bytes.extend(movement.to_be_bytes());
+    ///     bytes.extend(weight.to_be_bytes());
+    ///     bytes.extend([0; 4]);
+    /// }
+    /// let book = Book::from_bytes(&bytes).unwrap();
+    ///
+    /// let entries = book.entries(&Board::new());
+    /// assert_eq!(entries.len(), 2);
+    /// assert_eq!(entries[0].movement, "e2e4".parse().unwrap());
+    /// assert_eq!(entries[1].movement, "d2d4".parse().unwrap());
+    /// assert_eq!(book.best(&Board::new()), Some("e2e4".parse().unwrap()));
+    /// assert!(book.pick(&Board::new()).is_some());
+    ///
+    /// assert!(Book::from_bytes(&bytes[..20]).is_err());
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Book, &'static str> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err("A Polyglot book consists of entries of 16 bytes.");
+        }
+        let mut records: Vec<Record> = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|entry| {
+                let number = |range: std::ops::Range<usize>| {
+                    entry[range]
+                        .iter()
+                        .fold(0_u64, |n, &b| n << 8 | u64::from(b))
+                };
+                Record {
+                    key: number(0..8),
+                    movement: number(8..10) as u16,
+                    weight: number(10..12) as u16,
+                    learn: number(12..16) as u32,
+                }
+            })
+            .collect();
+        // Books are written sorted, but a lookup relies on it
+        records.sort_by_key(|r| r.key);
+        Ok(Book { records })
+    }
+
+    /// Reads the book in the file at `path`.
+    pub fn open(path: &Path) -> io::Result<Book> {
+        let bytes = fs::read(path)?;
+        Book::from_bytes(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Returns the contents of a `.bin` file holding the book.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.records.len() * ENTRY_SIZE);
+        for r in &self.records {
+            bytes.extend(r.key.to_be_bytes());
+            bytes.extend(r.movement.to_be_bytes());
+            bytes.extend(r.weight.to_be_bytes());
+            bytes.extend(r.learn.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Writes the book to the file at `path`.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns the legal movements the book has for the position of `board`, the ones
+    /// with the highest weight first.
+    pub fn entries(&self, board: &Board) -> Vec<Entry> {
+        let key = board.polyglot_key();
+        let start = self.records.partition_point(|r| r.key < key);
+        let legal = board.legal_moves();
+        let mut entries: Vec<Entry> = self.records[start..]
+            .iter()
+            .take_while(|r| r.key == key)
+            .filter_map(|r| {
+                let movement = decode(board, r.movement)?;
+                // Entries of another position with the same key are left out
+                legal.contains(&movement).then_some(Entry {
+                    movement,
+                    weight: r.weight,
+                    learn: r.learn,
+                })
+            })
+            .collect();
+        entries.sort_by_key(|e| Reverse(e.weight));
+        entries
+    }
+
+    /// Returns the movement with the highest weight, or `None` if the book has no
+    /// movement with a weight above 0 for the position.
+    pub fn best(&self, board: &Board) -> Option<Move> {
+        self.entries(board)
+            .first()
+            .filter(|e| e.weight > 0)
+            .map(|e| e.movement)
+    }
+
+    /// Picks one of the movements at random, each as often as its weight says,
+    /// or returns `None` if the book has no movement with a weight above 0.
+    pub fn pick(&self, board: &Board) -> Option<Move> {
+        let entries = self.entries(board);
+        let total: u64 = entries.iter().map(|e| u64::from(e.weight)).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut choice = RandomState::new().hash_one(board.key()) % total;
+        for e in entries {
+            if choice < u64::from(e.weight) {
+                return Some(e.movement);
+            }
+            choice -= u64::from(e.weight);
+        }
+        None
+    }
+}
+
+/// Collects the movements of games into a book. Every movement played in at least
+/// `min_games` of the games within their first `max_ply` half-moves gets an entry, weighted
+/// by the results of the games for the side which played it: 2 points for a win, 1 for
+/// a draw or an unknown result like `*` and none for a loss.
+///
+/// # Example
+///
+/// ```
+/// use rust_chess::core::board::Board;
+/// use rust_chess::core::pgn;
+/// use rust_chess::engine::book::BookBuilder;
+///
+/// let games = pgn::parse(
+///     "1. e4 e5 2. Nf3 1-0\n\n1. e4 c5 1/2-1/2\n\n1. d4 d5 0-1\n\n1. e4 e5 0-1\n",
+/// )
+/// .unwrap();
+/// let mut builder = BookBuilder::new(3, 2);
+/// for game in &games {
+///     builder.add_game(game).unwrap();
+/// }
+/// let book = builder.build();
+///
+/// // 1. e4 scores 2 + 1 + 0, 1. d4 is played only once
+/// let entries = book.entries(&Board::new());
+/// assert_eq!(entries.len(), 1);
+/// assert_eq!((entries[0].movement, entries[0].weight), ("e2e4".parse().unwrap(), 3));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct BookBuilder {
+    max_ply: usize,
+    min_games: u32,
+    /// Number of games and points, by key of the position and movement
+    stats: HashMap<(u64, u16), (u32, u32)>,
+}
+
+impl BookBuilder {
+    pub fn new(max_ply: usize, min_games: u32) -> Self {
+        Self {
+            max_ply,
+            min_games,
+            stats: HashMap::new(),
+        }
+    }
+
+    /// Adds the movements of `game`. Returns `Ok(false)` for games of variants other than
+    /// standard chess (including Chess960), which Polyglot books can't hold, and an error
+    /// if a movement is illegal, then none of the movements of the game are added.
+    pub fn add_game(&mut self, game: &PgnGame) -> Result<bool, PgnError> {
+        let mut board = game.start()?;
+        if board.variant().name() != variant::Standard.name() || board.is_chess960() {
+            return Ok(false);
+        }
+        let mut played = Vec::new();
+        for (i, san) in game.moves.iter().take(self.max_ply).enumerate() {
+            let error = |reason: &str| PgnError {
+                line: game.line,
+                reason: format!("movement {} '{san}': {reason}", i + 1),
+            };
+            let m = board.parse_san(san).map_err(error)?;
+            played.push((
+                board.polyglot_key(),
+                encode(&board, m),
+                board.current_move(),
+            ));
+            board.make_move(m).map_err(error)?;
+        }
+
+        for (key, movement, side) in played {
+            let points = match (game.result.as_str(), side) {
+                ("1-0", Side::White) | ("0-1", Side::Black) => 2,
+                ("1-0", Side::Black) | ("0-1", Side::White) => 0,
+                _ => 1,
+            };
+            let (games, score) = self.stats.entry((key, movement)).or_default();
+            *games += 1;
+            *score += points;
+        }
+        Ok(true)
+    }
+
+    /// Returns the book of the movements played in enough games. The weights are scaled
+    /// down if the highest score doesn't fit into an entry.
+    pub fn build(&self) -> Book {
+        let highest = self
+            .stats
+            .values()
+            .map(|&(_, score)| score)
+            .max()
+            .unwrap_or(0);
+        let scale = |score: u32| {
+            if highest > u32::from(u16::MAX) {
+                (u64::from(score) * u64::from(u16::MAX) / u64::from(highest)) as u16
+            } else {
+                score as u16
+            }
+        };
+        let mut records: Vec<Record> = self
+            .stats
+            .iter()
+            .filter(|(_, &(games, _))| games >= self.min_games)
+            .map(|(&(key, movement), &(_, score))| Record {
+                key,
+                movement,
+                weight: scale(score),
+                learn: 0,
+            })
+            .collect();
+        records.sort_by_key(|r| (r.key, Reverse(r.weight), r.movement));
+        Book { records }
+    }
+}
+
+/// Encodes `movement` on `board` for an entry, the reverse of `decode`.
+fn encode(board: &Board, movement: Move) -> u16 {
+    let field = |pos: Pos| u16::from(pos.column as u8 - b'a') | u16::from(pos.row - 1) << 3;
+    let mut end = movement.end;
+    let king = board
+        .at(movement.start)
+        .is_some_and(|p| p.p_type() == Type::King);
+    if king && !board.is_chess960() && (movement.start.column as u8).abs_diff(end.column as u8) == 2
+    {
+        end.column = if end.column == 'g' { 'h' } else { 'a' };
+    }
+    let promotion = match movement.promotion {
+        Some(Type::Knight) => 1,
+        Some(Type::Bishop) => 2,
+        Some(Type::Rook) => 3,
+        Some(Type::Queen) => 4,
+        _ => 0,
+    };
+    field(end) | field(movement.start) << 6 | promotion << 12
+}
+
+/// Decodes a movement of an entry: the column and the row of the end field in bits 0-5,
+/// of the start field in bits 6-11 and the promotion (knight to queen as 1 to 4) in bits
+/// 12-14. Castling is written as the king capturing its own rook, so it is changed to the
+/// movement of the king by two fields unless `board` is played as Chess960.
+fn decode(board: &Board, movement: u16) -> Option<Move> {
+    let field = |bits: u16| Pos::new((b'a' + (bits & 7) as u8).into(), (bits >> 3 & 7) as u8 + 1);
+    let start = field(movement >> 6);
+    let mut end = field(movement);
+    let promotion = match movement >> 12 & 7 {
+        0 => None,
+        1 => Some(Type::Knight),
+        2 => Some(Type::Bishop),
+        3 => Some(Type::Rook),
+        4 => Some(Type::Queen),
+        _ => return None,
+    };
+
+    let moved = board.at(start)?;
+    let castling = board.at(end).is_some_and(|p| {
+        moved.p_type() == Type::King && p.p_type() == Type::Rook && p.p_side() == moved.p_side()
+    });
+    if castling && !board.is_chess960() {
+        let column = if end.column > start.column { 'g' } else { 'c' };
+        end = Pos::new(column, start.row);
+    }
+    Some(Move {
+        start,
+        end,
+        promotion,
+        drop: None,
+    })
+}
@@ -0,0 +1,173 @@
+use crate::core::board::{Board, IN_HAND};
+use crate::core::piece::{Side, Type};
+use crate::core::position::Pos;
+
+// Piece-square tables from the "Simplified Evaluation Function" by Tomasz Michniewski,
+// written from White's perspective with row 8 first, so that they read like a board.
+
+#[rustfmt::skip]
+const PAWN: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+const BISHOP: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const ROOK: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const KING_MIDDLEGAME: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+#[rustfmt::skip]
+const KING_ENDGAME: [i32; 64] = [
+    -50,-40,-30,-20,-20,-30,-40,-50,
+    -30,-20,-10,  0,  0,-10,-20,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-30,  0,  0,  0,  0,-30,-30,
+    -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+/// Evaluates the position statically in centipawns from White's perspective:
+/// positive values are good for White, negative ones for Black.
+/// The evaluation consists of the material, including pieces in hand, valued as the
+/// variant says, and the placement of the pieces.
+/// In Antichess, where a side wins by losing its pieces, having less material is better.
+///
+/// # Example
+///
+/// ```
+/// use rust_chess::core::board::Board;
+/// use rust_chess::core::variant;
+/// use rust_chess::engine::eval::evaluate;
+///
+/// assert_eq!(evaluate(&Board::new()), 0);
+///
+/// let b = Board::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+/// assert!(evaluate(&b) > 800);
+///
+/// let antichess = variant::by_name("antichess").unwrap();
+/// let b = Board::from_fen_variant("4k3/8/8/8/8/8/8/3QK3 w - - 0 1", antichess).unwrap();
+/// assert!(evaluate(&b) < -800);
+/// ```
+pub fn evaluate(board: &Board) -> i32 {
+    let variant = board.variant();
+    let mut score = 0;
+    let endgame = is_endgame(board);
+
+    for column in 'a'..='h' {
+        for row in 1..=8 {
+            let pos = Pos::new(column, row);
+            let Some(p) = board.at(pos) else {
+                continue;
+            };
+
+            // The tables start with row 8, so White's index is mirrored vertically
+            let index = match p.p_side() {
+                Side::White => Pos::at(column, 9 - row),
+                Side::Black => usize::from(pos),
+            };
+            let table = match p.p_type() {
+                Type::Pawn(_) => &PAWN,
+                Type::Knight => &KNIGHT,
+                Type::Bishop => &BISHOP,
+                Type::Rook => &ROOK,
+                Type::Queen => &QUEEN,
+                Type::King if endgame => &KING_ENDGAME,
+                Type::King => &KING_MIDDLEGAME,
+            };
+
+            let value = variant.piece_value(p.p_type()) + table[index];
+            match p.p_side() {
+                Side::White => score += value,
+                Side::Black => score -= value,
+            }
+        }
+    }
+    for t in IN_HAND {
+        let pieces = board.in_hand(Side::White, t) as i32 - board.in_hand(Side::Black, t) as i32;
+        score += pieces * variant.piece_value(t);
+    }
+    score
+}
+
+/// Returns true if there is little material left, so the kings should become active.
+/// Taken as both sides having no queens, or at most a queen and one minor piece.
+fn is_endgame(board: &Board) -> bool {
+    let mut material = [0; 2];
+    let mut queens = [0; 2];
+    for column in 'a'..='h' {
+        for row in 1..=8 {
+            if let Some(p) = board.at(Pos::new(column, row)) {
+                let side = p.p_side() as usize;
+                match p.p_type() {
+                    Type::Queen => queens[side] += 1,
+                    Type::Pawn(_) | Type::King => {}
+                    t => material[side] += t.value(),
+                }
+            }
+        }
+    }
+    (0..2).all(|side| queens[side] == 0 || material[side] <= Type::Knight.value())
+}
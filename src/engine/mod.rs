@@ -0,0 +1,3 @@
+pub mod book;
+pub mod eval;
+pub mod search;
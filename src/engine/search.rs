@@ -0,0 +1,615 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::core::board::{Board, Status};
+use crate::core::movement::Move;
+use crate::core::piece::{Side, Type};
+
+use super::eval;
+
+/// Score of a checkmate at the root, mates further away score less
+const MATE: i32 = 30_000;
+/// Scores above this value (in absolute terms) are mate scores
+const MATE_BOUND: i32 = MATE - 1_000;
+const INFINITY: i32 = MATE + 1;
+const MAX_PLY: usize = 128;
+/// Number of captures in the quiescence search a side can be forced into before the
+/// static evaluation is used anyway, so that long chains of captures stay affordable
+const FORCED_CAPTURES: u32 = 4;
+/// Number of entries of the transposition table
+const TABLE_SIZE: usize = 1 << 18;
+
+/// Conditions which stop the search. Without any of them the search only stops
+/// when the `stop` flag passed to `search` is set.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Limits {
+    /// Maximal depth in half-moves
+    pub depth: Option<u32>,
+    /// Maximal time to search
+    pub time: Option<Duration>,
+    /// Maximal number of visited positions
+    pub nodes: Option<u64>,
+}
+
+impl Limits {
+    /// Limits the search to `depth` half-moves
+    pub fn depth(depth: u32) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Default::default()
+        }
+    }
+
+    /// Limits the search to `time`
+    pub fn time(time: Duration) -> Self {
+        Self {
+            time: Some(time),
+            ..Default::default()
+        }
+    }
+
+    /// Chooses the time for one move from the `remaining` time on the clock,
+    /// the `increment` added after the move and the number of moves until the next time
+    /// control, if known.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rust_chess::engine::search::Limits;
+    ///
+    /// // Never more than the time left, however large the increment
+    /// let limits = Limits::from_clock(Duration::from_secs(60), Duration::MAX, None);
+    /// assert_eq!(limits.time, Some(Duration::from_millis(59_950)));
+    /// ```
+    pub fn from_clock(remaining: Duration, increment: Duration, moves_to_go: Option<u32>) -> Self {
+        let moves = moves_to_go.unwrap_or(30).max(1);
+        let time = (remaining / moves).saturating_add(increment.saturating_mul(3) / 4);
+        // Always keep a reserve, so that the flag doesn't fall because of overhead
+        let reserve = Duration::from_millis(50);
+        Self::time(
+            time.min(remaining.saturating_sub(reserve))
+                .max(Duration::from_millis(10)),
+        )
+    }
+}
+
+/// Evaluation of a position from the perspective of the side to move
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Score {
+    /// Advantage in centipawns
+    Centipawns(i32),
+    /// The side to move mates in the given number of moves,
+    /// or gets mated if the number is negative
+    Mate(i32),
+}
+
+impl Score {
+    fn from_internal(score: i32) -> Self {
+        if score > MATE_BOUND {
+            Score::Mate((MATE - score + 1) / 2)
+        } else if score < -MATE_BOUND {
+            Score::Mate(-(MATE + score + 1) / 2)
+        } else {
+            Score::Centipawns(score)
+        }
+    }
+
+    /// Returns the score from White's perspective, given the side to move.
+    pub fn for_white(self, side: Side) -> Score {
+        match (side, self) {
+            (Side::White, s) => s,
+            (Side::Black, Score::Centipawns(cp)) => Score::Centipawns(-cp),
+            (Side::Black, Score::Mate(n)) => Score::Mate(-n),
+        }
+    }
+}
+
+/// Writes the score in pawns like `+0.35`, or as `#3`/`#-3` for mates.
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Score::Centipawns(cp) => write!(f, "{:+.2}", *cp as f64 / 100.0),
+            Score::Mate(n) => write!(f, "#{n}"),
+        }
+    }
+}
+
+/// Result of a finished iteration of the search
+#[derive(Debug, Clone)]
+pub struct Info {
+    /// Depth in half-moves
+    pub depth: u32,
+    /// Highest depth reached by any line, including the capture sequences at the end
+    pub seldepth: u32,
+    pub score: Score,
+    /// Number of visited positions
+    pub nodes: u64,
+    pub time: Duration,
+    /// Principal variation: the best line found, starting with the best movement
+    pub pv: Vec<Move>,
+}
+
+impl Info {
+    /// Returns the best movement found
+    pub fn best_move(&self) -> Option<Move> {
+        self.pv.first().copied()
+    }
+
+    /// Returns the number of visited positions per second
+    pub fn nps(&self) -> u64 {
+        (self.nodes as f64 / self.time.as_secs_f64().max(0.001)) as u64
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    key: u64,
+    depth: u32,
+    score: i32,
+    bound: Bound,
+    best: Option<Move>,
+}
+
+/// Searches the best movement on `board` with iterative deepening alpha-beta search.
+/// `report` is called after every completed depth. The search ends when one of the `limits`
+/// is reached or `stop` is set, and the last completed iteration is returned.
+/// `None` is returned only if there are no legal movements.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::atomic::AtomicBool;
+///
+/// use rust_chess::core::board::Board;
+/// use rust_chess::engine::search::{search, Limits, Score};
+///
+/// // White mates with Qh5xf7
+/// let b = Board::from_fen(
+///     "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4"
+/// ).unwrap();
+/// let info = search(&b, &Limits::depth(3), &AtomicBool::new(false), |_| {}).unwrap();
+///
+/// assert_eq!(info.best_move(), Some("h5f7".parse().unwrap()));
+/// assert_eq!(info.score, Score::Mate(1));
+/// ```
+pub fn search<F>(board: &Board, limits: &Limits, stop: &AtomicBool, report: F) -> Option<Info>
+where
+    F: FnMut(&Info),
+{
+    search_excluding(board, limits, stop, &[], report)
+}
+
+/// Like `search`, but the movements in `excluded` are not considered at the root.
+/// Used to find the second, third, ... best line.
+pub fn search_excluding<F>(
+    board: &Board,
+    limits: &Limits,
+    stop: &AtomicBool,
+    excluded: &[Move],
+    mut report: F,
+) -> Option<Info>
+where
+    F: FnMut(&Info),
+{
+    search_root(board, limits, stop, excluded, 1, |lines| report(&lines[0])).pop()
+}
+
+/// Like `search`, but finds the best `lines` lines starting with different movements
+/// (multi-PV). `report` is called with the lines, best first, after every completed
+/// depth, and the lines of the last completed depth are returned. There are fewer lines
+/// if there are fewer legal movements.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::atomic::AtomicBool;
+///
+/// use rust_chess::core::board::Board;
+/// use rust_chess::engine::search::{search_lines, Limits};
+///
+/// let lines = search_lines(&Board::new(), &Limits::depth(2), &AtomicBool::new(false), 3, |_| {});
+///
+/// assert_eq!(lines.len(), 3);
+/// assert_ne!(lines[0].best_move(), lines[1].best_move());
+/// ```
+pub fn search_lines<F>(
+    board: &Board,
+    limits: &Limits,
+    stop: &AtomicBool,
+    lines: usize,
+    report: F,
+) -> Vec<Info>
+where
+    F: FnMut(&[Info]),
+{
+    search_root(board, limits, stop, &[], lines, report)
+}
+
+/// Searches the best `lines` lines at the root, leaving out the movements in `excluded`.
+fn search_root<F>(
+    board: &Board,
+    limits: &Limits,
+    stop: &AtomicBool,
+    excluded: &[Move],
+    lines: usize,
+    mut report: F,
+) -> Vec<Info>
+where
+    F: FnMut(&[Info]),
+{
+    let root_moves: Vec<Move> = board
+        .legal_moves()
+        .into_iter()
+        .filter(|m| !excluded.contains(m))
+        .collect();
+    let lines = lines.min(root_moves.len());
+    if lines == 0 {
+        return Vec::new();
+    }
+
+    let mut searcher = Searcher {
+        board: board.clone(),
+        limits: *limits,
+        stop,
+        start: Instant::now(),
+        nodes: 0,
+        seldepth: 0,
+        aborted: false,
+        table: vec![None; TABLE_SIZE],
+        killers: [[None; 2]; MAX_PLY],
+        pv: vec![Vec::new(); MAX_PLY + 1],
+    };
+
+    let mut best: Vec<Info> = Vec::new();
+    let max_depth = limits.depth.unwrap_or(MAX_PLY as u32 / 2).max(1);
+    for depth in 1..=max_depth {
+        let mut infos: Vec<Info> = Vec::with_capacity(lines);
+        for line in 0..lines {
+            // Every line starts with a movement not played by the better lines
+            let moves: Vec<Move> = root_moves
+                .iter()
+                .filter(|&&m| infos.iter().all(|info| info.best_move() != Some(m)))
+                .copied()
+                .collect();
+            let previous = best.get(line).and_then(Info::best_move);
+            searcher.seldepth = 0;
+            let score = searcher.root(&moves, previous, depth);
+            if searcher.aborted && !(best.is_empty() && infos.is_empty()) {
+                break;
+            }
+            infos.push(Info {
+                depth,
+                seldepth: searcher.seldepth,
+                score: Score::from_internal(score),
+                nodes: searcher.nodes,
+                time: searcher.start.elapsed(),
+                pv: searcher.pv[0].clone(),
+            });
+            if searcher.aborted {
+                break;
+            }
+        }
+        if searcher.aborted && !best.is_empty() {
+            break;
+        }
+
+        report(&infos);
+        let mate_found =
+            matches!(infos[0].score, Score::Mate(n) if n > 0 && (2 * n - 1) as u32 <= depth);
+        best = infos;
+
+        if searcher.aborted || mate_found && limits.depth.is_none() {
+            break;
+        }
+        // Another iteration would most likely not finish in the remaining time
+        if let Some(time) = limits.time {
+            if searcher.start.elapsed() > time / 2 {
+                break;
+            }
+        }
+    }
+    best
+}
+
+struct Searcher<'a> {
+    board: Board,
+    limits: Limits,
+    stop: &'a AtomicBool,
+    start: Instant,
+    nodes: u64,
+    seldepth: u32,
+    aborted: bool,
+    table: Vec<Option<Entry>>,
+    killers: [[Option<Move>; 2]; MAX_PLY],
+    /// Triangular table of the principal variations found at every ply
+    pv: Vec<Vec<Move>>,
+}
+
+impl Searcher<'_> {
+    /// Searches `moves` at the root, trying the best movement of the `previous` depth first.
+    fn root(&mut self, moves: &[Move], previous: Option<Move>, depth: u32) -> i32 {
+        let mut moves = moves.to_vec();
+        self.order(&mut moves, previous, 0);
+
+        let mut alpha = -INFINITY;
+        for m in moves {
+            self.board.make_move_unchecked(m);
+            let score = -self.negamax(depth - 1, 1, -INFINITY, -alpha);
+            self.board.unmake_move();
+
+            if self.aborted {
+                break;
+            }
+            if score > alpha {
+                alpha = score;
+                self.update_pv(0, m);
+            }
+        }
+        alpha
+    }
+
+    fn negamax(&mut self, depth: u32, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.pv[ply].clear();
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+
+        if self.board.repetitions() > 0 || self.board.halfmove_clock() >= 100 {
+            return 0;
+        }
+        if let Some(status) = self.board.variant().outcome(&self.board) {
+            return self.final_score(status, ply);
+        }
+
+        let in_check = self.board.in_check();
+        // Search checks deeper, so that forced lines aren't cut off too early
+        let depth = if in_check { depth + 1 } else { depth };
+        if depth == 0 || ply >= MAX_PLY - 1 {
+            return self.quiescence(ply, alpha, beta, FORCED_CAPTURES);
+        }
+
+        let key = self.board.key();
+        let index = (key as usize) % TABLE_SIZE;
+        let mut table_move = None;
+        if let Some(entry) = self.table[index].filter(|e| e.key == key) {
+            table_move = entry.best;
+            if entry.depth >= depth {
+                let score = from_table(entry.score, ply);
+                let cutoff = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => score >= beta,
+                    Bound::Upper => score <= alpha,
+                };
+                if cutoff {
+                    return score;
+                }
+            }
+        }
+
+        let mut moves = self.board.legal_moves();
+        if moves.is_empty() {
+            let status = self.board.variant().no_moves(&self.board);
+            return self.final_score(status, ply);
+        }
+        self.order(&mut moves, table_move, ply);
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        for m in moves {
+            let quiet = self.is_quiet(m);
+            self.board.make_move_unchecked(m);
+            let score = -self.negamax(depth - 1, ply + 1, -beta, -alpha);
+            self.board.unmake_move();
+
+            if self.aborted {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = Some(m);
+            }
+            if score > alpha {
+                alpha = score;
+                self.update_pv(ply, m);
+            }
+            if alpha >= beta {
+                if quiet && self.killers[ply][0] != Some(m) {
+                    self.killers[ply][1] = self.killers[ply][0];
+                    self.killers[ply][0] = Some(m);
+                }
+                break;
+            }
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table[index] = Some(Entry {
+            key,
+            depth,
+            score: to_table(best_score, ply),
+            bound,
+            best: best_move,
+        });
+        best_score
+    }
+
+    /// Searches only captures and promotions, so that the static evaluation isn't used
+    /// in the middle of an exchange. In variants where capturing is compulsory a side
+    /// which can capture has to, up to `forced` times.
+    fn quiescence(&mut self, ply: usize, mut alpha: i32, beta: i32, forced: u32) -> i32 {
+        self.pv[ply].clear();
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply as u32);
+        if let Some(status) = self.board.variant().outcome(&self.board) {
+            return self.final_score(status, ply);
+        }
+
+        let stand_pat = match self.board.current_move() {
+            Side::White => eval::evaluate(&self.board),
+            Side::Black => -eval::evaluate(&self.board),
+        };
+        // A side which has to capture can't stand pat, all its movements are searched
+        let legal = self.board.legal_moves();
+        let forced_now = forced > 0
+            && self.board.variant().compulsory_captures()
+            && legal.iter().any(|&m| self.board.is_capture(m));
+        if !forced_now {
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+            alpha = alpha.max(stand_pat);
+        }
+        if ply >= MAX_PLY - 1 {
+            return stand_pat.max(alpha);
+        }
+
+        // Captures losing material are left out, unless captures can do more than remove
+        // the captured piece, like exploding a king in Atomic chess
+        let prune = !forced_now && !self.board.variant().explosions();
+        let mut moves: Vec<Move> = legal
+            .into_iter()
+            .filter(|&m| forced_now || !self.is_quiet(m))
+            .filter(|m| !prune || m.promotion.is_some() || self.board.see(m) >= 0)
+            .collect();
+        self.order(&mut moves, None, ply);
+
+        for m in moves {
+            self.board.make_move_unchecked(m);
+            let left = forced - u32::from(forced_now);
+            let score = -self.quiescence(ply + 1, -beta, -alpha, left);
+            self.board.unmake_move();
+
+            if self.aborted {
+                return 0;
+            }
+            if score > alpha {
+                alpha = score;
+                self.update_pv(ply, m);
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+        alpha
+    }
+
+    /// Returns the score of a game which has ended with `status` for the side to move,
+    /// preferring faster wins and slower losses.
+    fn final_score(&self, status: Status, ply: usize) -> i32 {
+        match status.winner() {
+            Some(side) if side == self.board.current_move() => MATE - ply as i32,
+            Some(_) => -MATE + ply as i32,
+            None => 0,
+        }
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.aborted {
+            return true;
+        }
+        // Always finish the first movement at the root, so that there is a movement to play
+        if self.pv[0].is_empty() {
+            return false;
+        }
+        // Looking at the clock is comparatively slow, so do it only every 1024 nodes
+        if self.nodes.is_multiple_of(1024) {
+            let out_of_time = self.limits.time.is_some_and(|t| self.start.elapsed() >= t);
+            let out_of_nodes = self.limits.nodes.is_some_and(|n| self.nodes >= n);
+            if out_of_time || out_of_nodes || self.stop.load(Ordering::Relaxed) {
+                self.aborted = true;
+            }
+        }
+        self.aborted
+    }
+
+    fn update_pv(&mut self, ply: usize, m: Move) {
+        let (current, deeper) = self.pv.split_at_mut(ply + 1);
+        current[ply].clear();
+        current[ply].push(m);
+        current[ply].extend_from_slice(&deeper[0]);
+    }
+
+    /// Returns true if `m` neither captures nor promotes.
+    fn is_quiet(&self, m: Move) -> bool {
+        m.promotion.is_none() && !self.board.is_capture(m)
+    }
+
+    /// Sorts the movements so that the most promising ones are searched first: the best
+    /// movement known from earlier, then captures of valuable pieces by cheap ones,
+    /// then the movements which caused cutoffs at the same ply. Captures losing material
+    /// in the exchange come last.
+    fn order(&self, moves: &mut [Move], best: Option<Move>, ply: usize) {
+        moves.sort_by_cached_key(|&m| {
+            let mut score = 0;
+            if Some(m) == best {
+                score += 1_000_000;
+            }
+            if self.board.is_capture(m) {
+                // The end field of an en passant capture is empty
+                let victim = self
+                    .board
+                    .at(m.end)
+                    .map_or(Type::Pawn(false).value(), |p| p.p_type().value());
+                let see = self.board.see(&m);
+                if see >= 0 {
+                    let attacker = self.board.at(m.start).map_or(0, |p| p.p_type().value());
+                    score += 10_000 + 10 * victim - attacker / 10;
+                } else {
+                    score += see;
+                }
+            }
+            if let Some(t) = m.promotion {
+                score += 9_000 + t.value();
+            }
+            if self.killers[ply].contains(&Some(m)) {
+                score += 5_000;
+            }
+            -score
+        });
+    }
+}
+
+/// Mate scores are stored relative to the position in the table, not to the root
+fn to_table(score: i32, ply: usize) -> i32 {
+    if score > MATE_BOUND {
+        score + ply as i32
+    } else if score < -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn from_table(score: i32, ply: usize) -> i32 {
+    if score > MATE_BOUND {
+        score - ply as i32
+    } else if score < -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
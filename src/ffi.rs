@@ -0,0 +1,358 @@
+//! C interface to the rules and the engine, built with the `ffi` feature. The header
+//! `include/rust_chess.h` is generated by `cbindgen --config cbindgen.toml --output
+//! include/rust_chess.h` and can be used from C and C++.
+//!
+//! A board is created with `chess_board_new` or `chess_board_from_fen` and has to be
+//! released with `chess_board_free`. Fields are numbered from 0 for a1, 1 for b1, up to
+//! 63 for h8. Functions given a null board or an invalid movement fail like for an
+//! illegal one, and none of them lets a panic unwind into the caller.
+//!
+//! # Example
+//!
+//! ```
+//! use std::ptr;
+//! use rust_chess::ffi::*;
+//!
+//! unsafe {
+//!     let board = chess_board_new();
+//!     assert_eq!(chess_board_legal_moves(board, ptr::null_mut(), 0), 20);
+//!     let mut moves = [ChessMove { from: 0, to: 0, promotion: 0, drop: 0 }; 20];
+//!     chess_board_legal_moves(board, moves.as_mut_ptr(), moves.len());
+//!
+//!     // e2e4, then the illegal e4e5 and a field outside the board
+//!     let e2e4 = ChessMove { from: 12, to: 28, promotion: 0, drop: 0 };
+//!     assert!(moves.contains(&e2e4));
+//!     assert!(chess_board_make_move(board, e2e4));
+//!     assert!(!chess_board_make_move(board, ChessMove { from: 28, to: 36, ..e2e4 }));
+//!     assert!(!chess_board_make_move(board, ChessMove { from: 64, ..e2e4 }));
+//!     assert_eq!(chess_board_side_to_move(board), 1);
+//!
+//!     let mut fen = [0; 100];
+//!     let length = chess_board_fen(board, fen.as_mut_ptr(), fen.len());
+//!     let fen = std::ffi::CStr::from_ptr(fen.as_ptr()).to_str().unwrap();
+//!     assert_eq!(fen, "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+//!     assert_eq!(length, fen.len());
+//!     assert!(chess_board_unmake_move(board));
+//!     assert!(!chess_board_unmake_move(board));
+//!     chess_board_free(board);
+//!
+//!     // A king can't be dropped or a pawn promoted to a pawn
+//!     let board = chess_board_from_fen(
+//!         c"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1".as_ptr(),
+//!         c"crazyhouse".as_ptr(),
+//!     );
+//!     assert!(!board.is_null());
+//!     let e4 = 28;
+//!     let king = ChessMove { from: e4, to: e4, promotion: 0, drop: b'K' as _ };
+//!     assert!(!chess_board_make_move(board, king));
+//!     let pawn = ChessMove { from: 12, to: 28, promotion: b'P' as _, drop: 0 };
+//!     assert!(!chess_board_make_move(board, pawn));
+//!     chess_board_free(board);
+//!
+//!     assert!(chess_board_from_fen(c"8/8/8/8 w - - 0 1".as_ptr(), ptr::null()).is_null());
+//!     assert!(chess_board_from_fen(ptr::null(), ptr::null()).is_null());
+//!     assert!(chess_board_from_fen(c"".as_ptr(), c"shogi".as_ptr()).is_null());
+//!
+//!     // Null boards
+//!     let null = ptr::null_mut();
+//!     assert_eq!(chess_board_legal_moves(null, ptr::null_mut(), 0), 0);
+//!     assert!(!chess_board_make_move(null, e2e4));
+//!     assert!(!chess_board_unmake_move(null));
+//!     assert_eq!(chess_board_fen(null, ptr::null_mut(), 0), 0);
+//!     assert_eq!(chess_board_status(null), ChessStatus::Ongoing);
+//!     assert!(!chess_board_search(null, 10, ptr::null_mut()));
+//!     chess_board_free(null);
+//! }
+//! ```
+
+use std::ffi::{c_char, CStr};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use crate::core::board::Board;
+use crate::core::movement::Move;
+use crate::core::piece::{Side, Type};
+use crate::core::position::Pos;
+use crate::core::variant::{self, Variant};
+use crate::engine::search::{self, Limits};
+
+/// A position with the movements leading to it, handled through pointers
+pub struct ChessBoard {
+    board: Board,
+}
+
+/// A movement between two fields, numbered from 0 (a1) to 63 (h8). `promotion` and
+/// `drop` are uppercase piece letters like `'Q'`, or 0 if the movement doesn't promote
+/// or drop a piece. For a drop from the hand `from` equals `to`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChessMove {
+    pub from: u8,
+    pub to: u8,
+    pub promotion: c_char,
+    pub drop: c_char,
+}
+
+/// Whether the game goes on or how it has ended, see `chess_board_status`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChessStatus {
+    Ongoing,
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl From<Move> for ChessMove {
+    fn from(m: Move) -> Self {
+        let letter = |t: Option<Type>| t.map_or(0, |t| t.letter() as c_char);
+        ChessMove {
+            from: usize::from(m.start) as u8,
+            to: usize::from(m.end) as u8,
+            promotion: letter(m.promotion),
+            drop: letter(m.drop),
+        }
+    }
+}
+
+impl ChessMove {
+    /// Returns the movement, or `None` if a field or a piece letter is invalid. Pawns
+    /// may be promoted to kings in Antichess, but kings are never dropped.
+    fn to_move(self) -> Option<Move> {
+        let field =
+            |index: u8| (index < 64).then(|| Pos::new((b'a' + index % 8).into(), index / 8 + 1));
+        let piece = |letter: c_char| match letter {
+            0 => Some(None),
+            letter => Type::from_letter(letter as u8 as char).map(Some),
+        };
+        let (start, end) = (field(self.from)?, field(self.to)?);
+        let (promotion, drop) = (piece(self.promotion)?, piece(self.drop)?);
+        if matches!(promotion, Some(Type::Pawn(_))) || drop == Some(Type::King) {
+            return None;
+        }
+        Some(match drop {
+            Some(t) => Move::drop(t, end),
+            None => Move {
+                start,
+                end,
+                promotion,
+                drop: None,
+            },
+        })
+    }
+}
+
+/// Runs `f`, returning `fallback` if it panics, since a panic can't unwind into C.
+fn guard<T>(fallback: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+/// Creates a board with the starting position of standard chess.
+#[no_mangle]
+pub extern "C" fn chess_board_new() -> *mut ChessBoard {
+    Box::into_raw(Box::new(ChessBoard {
+        board: Board::new(),
+    }))
+}
+
+/// Creates a board with the position `fen`, played by the rules of `variant`, like
+/// `"atomic"`, or of standard chess if it is null. Returns null if the FEN or the
+/// variant is invalid.
+///
+/// # Safety
+///
+/// `fen` has to be a null-terminated string, `variant` one as well or null.
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_from_fen(
+    fen: *const c_char,
+    variant: *const c_char,
+) -> *mut ChessBoard {
+    guard(ptr::null_mut(), || {
+        let rules: &dyn Variant = if variant.is_null() {
+            &variant::Standard
+        } else {
+            match CStr::from_ptr(variant)
+                .to_str()
+                .ok()
+                .and_then(variant::by_name)
+            {
+                Some(rules) => rules,
+                None => return ptr::null_mut(),
+            }
+        };
+        if fen.is_null() {
+            return ptr::null_mut();
+        }
+        match CStr::from_ptr(fen)
+            .to_str()
+            .ok()
+            .and_then(|fen| Board::from_fen_variant(fen, rules).ok())
+        {
+            Some(board) => Box::into_raw(Box::new(ChessBoard { board })),
+            None => ptr::null_mut(),
+        }
+    })
+}
+
+/// Releases `board`. Null is ignored.
+///
+/// # Safety
+///
+/// `board` has to be returned by `chess_board_new` or `chess_board_from_fen` and
+/// can't be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_free(board: *mut ChessBoard) {
+    if !board.is_null() {
+        drop(Box::from_raw(board));
+    }
+}
+
+/// Writes up to `capacity` legal movements of the side to move to `moves` and returns
+/// how many there are, which may be more than `capacity`. Returns 0 for a null board.
+///
+/// # Safety
+///
+/// `board` has to be a valid board or null and `moves` has to point to space for
+/// `capacity` movements, nothing is written if it is null.
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_legal_moves(
+    board: *const ChessBoard,
+    moves: *mut ChessMove,
+    capacity: usize,
+) -> usize {
+    let Some(board) = board.as_ref() else {
+        return 0;
+    };
+    guard(0, || {
+        let legal = board.board.legal_moves();
+        if !moves.is_null() && capacity > 0 {
+            let buffer = slice::from_raw_parts_mut(moves, capacity);
+            for (slot, &m) in buffer.iter_mut().zip(&legal) {
+                *slot = m.into();
+            }
+        }
+        legal.len()
+    })
+}
+
+/// Performs `movement` if it is legal, returning whether it was performed.
+///
+/// # Safety
+///
+/// `board` has to be a valid board or null.
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_make_move(
+    board: *mut ChessBoard,
+    movement: ChessMove,
+) -> bool {
+    let (Some(board), Some(m)) = (board.as_mut(), movement.to_move()) else {
+        return false;
+    };
+    guard(false, || board.board.make_move(m).is_ok())
+}
+
+/// Takes back the last movement, returning false if there was none.
+///
+/// # Safety
+///
+/// `board` has to be a valid board or null.
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_unmake_move(board: *mut ChessBoard) -> bool {
+    let Some(board) = board.as_mut() else {
+        return false;
+    };
+    guard(false, || board.board.unmake_move().is_some())
+}
+
+/// Writes the FEN of the position as a null-terminated string to `buffer` if it fits
+/// into `capacity` bytes, and returns its length without the terminating null, like
+/// `snprintf`. Returns 0 for a null board.
+///
+/// # Safety
+///
+/// `board` has to be a valid board or null and `buffer` has to point to `capacity`
+/// bytes, nothing is written if it is null.
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_fen(
+    board: *const ChessBoard,
+    buffer: *mut c_char,
+    capacity: usize,
+) -> usize {
+    let Some(board) = board.as_ref() else {
+        return 0;
+    };
+    guard(0, || {
+        let fen = board.board.to_fen();
+        if !buffer.is_null() && fen.len() < capacity {
+            ptr::copy_nonoverlapping(fen.as_ptr().cast(), buffer, fen.len());
+            *buffer.add(fen.len()) = 0;
+        }
+        fen.len()
+    })
+}
+
+/// Returns the side to move, 0 for White and 1 for Black, also for a null board.
+///
+/// # Safety
+///
+/// `board` has to be a valid board or null.
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_side_to_move(board: *const ChessBoard) -> u8 {
+    match board.as_ref().map(|b| b.board.current_move()) {
+        Some(Side::White) | None => 0,
+        Some(Side::Black) => 1,
+    }
+}
+
+/// Returns whether the game goes on or who has won, `Ongoing` for a null board.
+///
+/// # Safety
+///
+/// `board` has to be a valid board or null.
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_status(board: *const ChessBoard) -> ChessStatus {
+    let Some(board) = board.as_ref() else {
+        return ChessStatus::Ongoing;
+    };
+    guard(ChessStatus::Ongoing, || {
+        let status = board.board.status();
+        match status.winner() {
+            Some(Side::White) => ChessStatus::WhiteWins,
+            Some(Side::Black) => ChessStatus::BlackWins,
+            None if !status.is_over() => ChessStatus::Ongoing,
+            None => ChessStatus::Draw,
+        }
+    })
+}
+
+/// Searches the best movement for at most `millis` milliseconds and writes it to `best`.
+/// Returns false if the game is over or a pointer is null.
+///
+/// # Safety
+///
+/// `board` has to be a valid board or null and `best` has to point to a movement or
+/// be null.
+#[no_mangle]
+pub unsafe extern "C" fn chess_board_search(
+    board: *const ChessBoard,
+    millis: u32,
+    best: *mut ChessMove,
+) -> bool {
+    let (Some(board), Some(best)) = (board.as_ref(), best.as_mut()) else {
+        return false;
+    };
+    guard(false, || {
+        let limits = Limits::time(Duration::from_millis(millis.into()));
+        let info = search::search(&board.board, &limits, &AtomicBool::new(false), |_| {});
+        match info.and_then(|i| i.best_move()) {
+            Some(m) => {
+                *best = m.into();
+                true
+            }
+            None => false,
+        }
+    })
+}
@@ -1,2 +1,16 @@
 pub mod core;
+pub mod engine;
+// Terminal, network and protocol code needs a terminal, threads or sockets, which
+// the browser doesn't offer
+#[cfg(not(target_arch = "wasm32"))]
+pub mod net;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod protocol;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod ui;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "python")]
+pub mod python;
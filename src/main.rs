@@ -1,10 +1,478 @@
-#[cfg(all(feature = "unicode", feature = "ascii"))]
-compile_error!("feature \"unicode\" and feature \"ascii\" cannot be enabled at the same time");
+// The command-line program needs a terminal, threads and sockets, which the browser
+// doesn't offer, so nothing is built for WebAssembly
+#![cfg_attr(target_arch = "wasm32", no_main)]
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::collections::hash_map::RandomState;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::hash::BuildHasher;
+use std::io::{self, IsTerminal, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
 
 use rust_chess::core::board::Board;
-use rust_chess::ui::display;
+use rust_chess::core::pgn;
+use rust_chess::core::piece::Side;
+use rust_chess::engine::book::{Book, BookBuilder};
+use rust_chess::engine::search::Limits;
+use rust_chess::net::{http, server, Connection, Message, Offer};
+use rust_chess::protocol::{uci, xboard};
+use rust_chess::ui::cli::{self, Subcommand};
+use rust_chess::ui::game::{Game, Player, Settings, TimeControl};
+use rust_chess::ui::render::{GlyphSet, Renderer};
+use rust_chess::ui::{analysis, display, input, render};
+
+/// Exit code of failures like an invalid PGN file or an I/O error
+const FAILURE: u8 = 1;
+/// Exit code of invalid command-line arguments
+const USAGE_ERROR: u8 = 2;
+
+fn main() -> ExitCode {
+    let subcommand = match cli::parse(env::args().skip(1)) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            eprintln!("{}", cli::help(e.subcommand));
+            return ExitCode::from(USAGE_ERROR);
+        }
+    };
+
+    match subcommand {
+        Subcommand::Play {
+            board,
+            settings,
+            resume,
+            book,
+            glyphs,
+            no_color,
+            tui,
+            coach,
+        } => {
+            let mut game = match resume {
+                Some(path) => match Game::load(&path) {
+                    Ok(game) => game,
+                    Err(e) => {
+                        eprintln!("Error: loading '{}' failed: {e}", path.display());
+                        return ExitCode::from(FAILURE);
+                    }
+                },
+                None => Game::new(board, settings),
+            };
+            if let Some(path) = book {
+                match Book::open(&path) {
+                    Ok(book) => game.book = Some(book),
+                    Err(e) => {
+                        eprintln!("Error: reading the book '{}' failed: {e}", path.display());
+                        return ExitCode::from(FAILURE);
+                    }
+                }
+            }
+            if tui {
+                return run_tui(&mut game, glyphs);
+            }
+            let renderer = render::choose(glyphs, no_color);
+            display::game_loop(&mut game, renderer.as_ref(), coach, None);
+            ExitCode::SUCCESS
+        }
+        Subcommand::Perft {
+            board,
+            depth,
+            divide,
+        } => {
+            // A closed pipe, like piping into `head`, just means nobody reads the rest
+            match perft(&board, depth, divide) {
+                Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+                    eprintln!("Error: {e}");
+                    ExitCode::from(FAILURE)
+                }
+                _ => ExitCode::SUCCESS,
+            }
+        }
+        Subcommand::Analyze {
+            board,
+            limits,
+            lines,
+        } => {
+            analyze(&board, &limits, lines);
+            ExitCode::SUCCESS
+        }
+        Subcommand::PgnValidate { file } => validate_pgn(&file),
+        Subcommand::BookBuild {
+            input,
+            output,
+            max_ply,
+            min_games,
+        } => build_book(&input, &output, max_ply, min_games),
+        Subcommand::Uci => match uci::run() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                ExitCode::from(FAILURE)
+            }
+        },
+        Subcommand::Xboard => match xboard::run() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                ExitCode::from(FAILURE)
+            }
+        },
+        Subcommand::Host {
+            board,
+            port,
+            side,
+            time,
+            glyphs,
+            no_color,
+            coach,
+        } => {
+            let renderer = render::choose(glyphs, no_color);
+            match host(board, port, side, time, renderer.as_ref(), coach) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    ExitCode::from(FAILURE)
+                }
+            }
+        }
+        Subcommand::Join {
+            address,
+            glyphs,
+            no_color,
+            coach,
+        } => {
+            let renderer = render::choose(glyphs, no_color);
+            match join(&address, renderer.as_ref(), coach) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    ExitCode::from(FAILURE)
+                }
+            }
+        }
+        Subcommand::Serve { port, archive } => match TcpListener::bind(("0.0.0.0", port)) {
+            Ok(listener) => {
+                println!(
+                    "Serving games on port {port}, archiving them to '{}'.",
+                    archive.display()
+                );
+                server::run(listener, archive);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Error: listening on port {port} failed: {e}");
+                ExitCode::from(FAILURE)
+            }
+        },
+        Subcommand::Api { port } => match TcpListener::bind(("127.0.0.1", port)) {
+            Ok(listener) => {
+                println!("Answering HTTP requests on http://127.0.0.1:{port}/.");
+                http::run(listener);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Error: listening on port {port} failed: {e}");
+                ExitCode::from(FAILURE)
+            }
+        },
+        Subcommand::Help(subcommand) => {
+            println!("{}", cli::help(subcommand));
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+#[cfg(feature = "tui")]
+fn run_tui(game: &mut Game, glyphs: Option<GlyphSet>) -> ExitCode {
+    match rust_chess::ui::tui::run(game, glyphs.unwrap_or_default()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::from(FAILURE)
+        }
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_tui(_: &mut Game, _: Option<GlyphSet>) -> ExitCode {
+    eprintln!("Error: '--tui' requires building with the 'tui' feature");
+    ExitCode::from(USAGE_ERROR)
+}
+
+fn perft(board: &Board, depth: u32, divide: bool) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    let start = Instant::now();
+    let nodes = if divide {
+        let counts = board.perft_divide(depth);
+        for (m, count) in &counts {
+            writeln!(out, "{m}: {count}")?;
+        }
+        writeln!(out)?;
+        counts.iter().map(|(_, count)| count).sum()
+    } else {
+        board.perft(depth)
+    };
+    let time = start.elapsed();
+    writeln!(out, "Nodes: {nodes}")?;
+    writeln!(
+        out,
+        "Time: {:.3} s ({:.0} nodes/s)",
+        time.as_secs_f64(),
+        nodes as f64 / time.as_secs_f64().max(0.001)
+    )
+}
+
+fn analyze(board: &Board, limits: &Limits, lines: usize) {
+    let stop = Arc::new(AtomicBool::new(false));
+    if io::stdin().is_terminal() {
+        println!("Press Enter to stop.");
+        // The thread is left waiting for input if the search ends first
+        let stop = Arc::clone(&stop);
+        thread::spawn(move || {
+            let _ = io::stdin().read_line(&mut String::new());
+            stop.store(true, Ordering::Relaxed);
+        });
+    }
+    match analysis::analyze(board, limits, lines, &stop).first() {
+        Some(info) => {
+            let best = info
+                .best_move()
+                .expect("a finished search has a best movement");
+            println!("Best move: {}", board.san(best).unwrap_or_default());
+        }
+        None => println!("No legal moves: {}.", board.status()),
+    }
+}
+
+/// Waits for another player on `port`, offers them the game and plays it if they accept.
+/// The host plays `side`, or a random one if it is `None`.
+fn host(
+    board: Board,
+    port: u16,
+    side: Option<Side>,
+    time: Option<TimeControl>,
+    renderer: &dyn Renderer,
+    coach: bool,
+) -> Result<(), Box<dyn Error>> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    println!(
+        "Waiting for an opponent on port {}...",
+        listener.local_addr()?.port()
+    );
+    let mut connection = Connection::accept(&listener)?;
+
+    let side = side.unwrap_or_else(|| {
+        // The hasher is seeded randomly for every process
+        if RandomState::new().hash_one(port).is_multiple_of(2) {
+            Side::White
+        } else {
+            Side::Black
+        }
+    });
+    let offer = Offer {
+        side: side.opposite(),
+        time,
+        board: board.clone(),
+    };
+    connection.send(&Message::Offer(offer))?;
+    println!(
+        "Offered the opponent to play {}, waiting for an answer...",
+        side.opposite()
+    );
+    match connection.receive()? {
+        Message::Accept => println!("The opponent accepted, you play {side}."),
+        Message::Decline => {
+            println!("The opponent declined the game.");
+            return Ok(());
+        }
+        message => return Err(format!("unexpected message '{message}'").into()),
+    }
+
+    play_remote(board, side, time, renderer, coach, connection);
+    Ok(())
+}
+
+/// Connects to the game hosted at `address` and plays it if the player accepts the offer.
+fn join(address: &str, renderer: &dyn Renderer, coach: bool) -> Result<(), Box<dyn Error>> {
+    let mut connection = Connection::connect(address)?;
+    let offer = match connection.receive() {
+        Ok(Message::Offer(offer)) => offer,
+        Ok(message) => return Err(format!("unexpected message '{message}'").into()),
+        // Like a time control too long for the clocks, which isn't played
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            connection.send(&Message::Decline)?;
+            return Err(format!("the host has offered an invalid game: {e}").into());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    println!(
+        "The host offers a game of {}.",
+        offer.board.variant().name()
+    );
+    println!("You play {}.", offer.side);
+    match offer.time {
+        Some(tc) => println!("Time control: {tc}"),
+        None => println!("Time control: none"),
+    }
+    println!("Starting position: {}", offer.board.to_fen());
+    let accepted = input::confirm("Play this game?").unwrap_or(false);
+    let answer = if accepted {
+        Message::Accept
+    } else {
+        Message::Decline
+    };
+    connection.send(&answer)?;
+
+    if accepted {
+        play_remote(
+            offer.board,
+            offer.side,
+            offer.time,
+            renderer,
+            coach,
+            connection,
+        );
+    }
+    Ok(())
+}
+
+/// Plays the game starting at `board` against the player at the other end of
+/// `connection`, with the player at this machine playing `side`.
+fn play_remote(
+    board: Board,
+    side: Side,
+    time: Option<TimeControl>,
+    renderer: &dyn Renderer,
+    coach: bool,
+    mut connection: Connection,
+) {
+    let mut settings = Settings {
+        white: Player::Remote,
+        black: Player::Remote,
+        time,
+    };
+    match side {
+        Side::White => settings.white = Player::Human,
+        Side::Black => settings.black = Player::Human,
+    }
+    let mut game = Game::new(board, settings);
+    println!();
+    display::game_loop(&mut game, renderer, coach, Some(&mut connection));
+}
+
+/// Builds an opening book from the games of the PGN file `input`, or of the `.pgn` files
+/// in the folder `input`, and writes it to `output`. Files and games which can't be read
+/// are reported and left out.
+fn build_book(input: &Path, output: &Path, max_ply: usize, min_games: u32) -> ExitCode {
+    let files = if input.is_dir() {
+        let entries = match fs::read_dir(input) {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("Error: reading '{}' failed: {e}", input.display());
+                return ExitCode::from(FAILURE);
+            }
+        };
+        let mut files: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("pgn"))
+            })
+            .collect();
+        files.sort();
+        files
+    } else {
+        vec![input.to_path_buf()]
+    };
+
+    let mut builder = BookBuilder::new(max_ply, min_games);
+    let (mut used, mut skipped) = (0, 0);
+    for file in &files {
+        let games = match fs::read_to_string(file)
+            .map_err(|e| e.to_string())
+            .and_then(|text| pgn::parse(&text).map_err(|e| e.to_string()))
+        {
+            Ok(games) => games,
+            Err(e) => {
+                eprintln!("{}: {e}, skipped", file.display());
+                continue;
+            }
+        };
+        for game in &games {
+            match builder.add_game(game) {
+                Ok(true) => used += 1,
+                Ok(false) => skipped += 1,
+                Err(e) => {
+                    eprintln!("{}: {e}, game skipped", file.display());
+                    skipped += 1;
+                }
+            }
+        }
+    }
+
+    if used == 0 {
+        eprintln!("Error: no games to build the book from");
+        return ExitCode::from(FAILURE);
+    }
+    let book = builder.build();
+    if let Err(e) = book.save(output) {
+        eprintln!("Error: writing '{}' failed: {e}", output.display());
+        return ExitCode::from(FAILURE);
+    }
+    println!(
+        "{used} games from {} files used, {skipped} skipped; {} entries written to {}",
+        files.len(),
+        book.len(),
+        output.display()
+    );
+    ExitCode::SUCCESS
+}
+
+fn validate_pgn(file: &Path) -> ExitCode {
+    let text = match fs::read_to_string(file) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Error: reading '{}' failed: {e}", file.display());
+            return ExitCode::from(FAILURE);
+        }
+    };
+    let games = match pgn::parse(&text) {
+        Ok(games) => games,
+        Err(e) => {
+            eprintln!("{}: {e}", file.display());
+            return ExitCode::from(FAILURE);
+        }
+    };
+
+    let mut invalid = 0;
+    for (i, game) in games.iter().enumerate() {
+        match game.replay() {
+            Ok(_) => println!(
+                "Game {} (line {}): {} moves, {}",
+                i + 1,
+                game.line,
+                game.moves.len(),
+                game.result
+            ),
+            Err(e) => {
+                invalid += 1;
+                println!("Game {}: invalid, {e}", i + 1);
+            }
+        }
+    }
+    println!("{} games, {invalid} invalid", games.len());
 
-fn main() {
-    let mut b = Box::new(Board::new());
-    display::game_loop(&mut b);
+    if invalid == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(FAILURE)
+    }
 }
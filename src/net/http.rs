@@ -0,0 +1,329 @@
+//! A small HTTP service exposing the rules and the engine to programs written in other
+//! languages. Every endpoint takes a JSON object with `POST` and answers with one.
+//! The position is given as `fen`, the starting position if it is missing, played by
+//! the rules of the optional `variant`.
+//!
+//! - `POST /legal-moves` with `{"fen": "<FEN>"}` answers
+//!   `{"moves": [{"uci": "e2e4", "san": "e4"}, ...]}`.
+//! - `POST /move` with `{"fen": "<FEN>", "move": "e2e4"}`, the movement in UCI notation
+//!   or SAN, answers `{"fen": "<FEN>", "san": "e4", "check": false, "status": "ongoing",
+//!   "result": "*", "reason": null}`. The status is one of `ongoing`, `checkmate`,
+//!   `stalemate`, `fifty-move-rule`, `insufficient-material` and `variant-win`, the
+//!   reason describes how the game has ended. An illegal movement is answered with 422.
+//! - `POST /analyze` with `{"fen": "<FEN>", "depth": 8, "time": 2.5, "lines": 1}`, all
+//!   limits optional and the search taking 1 second without any and 60 at most, answers
+//!   `{"best": "e2e4", "depth": 8, "nodes": 123456, "lines": [{"score": {"cp": 30},
+//!   "pv": ["e2e4", "e7e5"], "san": "1. e4 e5"}]}`. Scores are from White's perspective,
+//!   a mate is written as `{"mate": 3}`, negative if Black mates.
+//! - `POST /pgn/validate` with `{"pgn": "<PGN text>"}` answers `{"valid": true, "games":
+//!   [{"line": 1, "moves": 4, "result": "0-1", "error": null}]}`, or `{"valid": false,
+//!   "error": "line 3: ..."}` if the text can't be read.
+//!
+//! Invalid requests, including an invalid FEN, are answered with 400 and
+//! `{"error": "..."}`.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::AtomicBool;
+use std::thread;
+use std::time::Duration;
+
+use crate::core::board::Board;
+use crate::core::movement::Move;
+use crate::core::pgn;
+use crate::core::variant::{self, Variant};
+use crate::engine::search::{self, Info, Limits, Score};
+
+use super::json::Value;
+
+/// Port of the service when none is given
+pub const DEFAULT_PORT: u16 = 8080;
+
+/// Largest request body accepted, in bytes
+const MAX_BODY: usize = 1 << 20;
+/// Largest number of header lines accepted
+const MAX_HEADERS: usize = 100;
+/// Search time of `/analyze` without limits
+const ANALYZE_TIME: Duration = Duration::from_secs(1);
+/// Longest search time `/analyze` accepts
+const MAX_ANALYZE_TIME: Duration = Duration::from_secs(60);
+
+/// Answers the requests of clients on `listener`, each connection on its own thread.
+pub fn run(listener: TcpListener) {
+    for stream in listener.incoming() {
+        // A client may have given up before its connection was accepted
+        let Ok(stream) = stream else {
+            continue;
+        };
+        thread::spawn(move || {
+            // A client which has left doesn't need an answer
+            let _ = serve(stream);
+        });
+    }
+}
+
+/// Reads a single request from `stream` and answers it, closing the connection.
+fn serve(stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let (status, body) = match read_request(&mut reader) {
+        Ok((method, path, body)) => handle(&method, &path, &body),
+        Err(e) => (400, error(&e.to_string())),
+    };
+    let body = body.to_string();
+    let mut writer = stream;
+    write!(
+        writer,
+        "HTTP/1.1 {status} {}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        reason(status),
+        body.len()
+    )?;
+    writer.flush()
+}
+
+/// Reads the method, the path and the body of a request.
+fn read_request(reader: &mut impl BufRead) -> io::Result<(String, String, String)> {
+    let invalid = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, reason.to_string());
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut words = line.split_whitespace();
+    let (Some(method), Some(path), Some(_version)) = (words.next(), words.next(), words.next())
+    else {
+        return Err(invalid("invalid request line"));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut length = 0;
+    for _ in 0..MAX_HEADERS {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body)?;
+            let body = String::from_utf8(body).map_err(|_| invalid("body isn't UTF-8"))?;
+            return Ok((method, path, body));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|&l| l <= MAX_BODY)
+                    .ok_or_else(|| invalid("invalid or too large Content-Length"))?;
+            }
+        }
+    }
+    Err(invalid("too many headers"))
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        422 => "Unprocessable Entity",
+        _ => "",
+    }
+}
+
+fn error(message: &str) -> Value {
+    Value::object([("error", message.into())])
+}
+
+/// Answers a request with the status code and the JSON body of the response.
+///
+/// # Example
+///
+/// ```
+/// use rust_chess::net::http::handle;
+///
+/// let (status, body) = handle("POST", "/move", r#"{"move": "e4"}"#);
+/// assert_eq!(status, 200);
+/// assert_eq!(
+///     body.get("fen").and_then(|f| f.as_str()),
+///     Some("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
+/// );
+///
+/// let (status, _) = handle("POST", "/move", r#"{"move": "e2e5"}"#);
+/// assert_eq!(status, 422);
+///
+/// let (status, _) = handle("POST", "/analyze", r#"{"depth": 1, "time": 1e30}"#);
+/// assert_eq!(status, 200);
+/// let (status, _) = handle("POST", "/analyze", r#"{"depth": 1, "lines": 0.5}"#);
+/// assert_eq!(status, 400);
+/// ```
+pub fn handle(method: &str, path: &str, body: &str) -> (u16, Value) {
+    let endpoint: fn(&Value) -> Result<Value, (u16, String)> = match path {
+        "/legal-moves" => legal_moves,
+        "/move" => play,
+        "/analyze" => analyze,
+        "/pgn/validate" => validate_pgn,
+        _ => return (404, error(&format!("unknown path '{path}'"))),
+    };
+    if method != "POST" {
+        return (405, error("only POST is supported"));
+    }
+    let request = match Value::parse(body) {
+        Ok(request @ Value::Object(_)) => request,
+        Ok(_) => return (400, error("the body has to be a JSON object")),
+        Err(e) => return (400, error(&format!("invalid JSON: {e}"))),
+    };
+    match endpoint(&request) {
+        Ok(response) => (200, response),
+        Err((status, message)) => (status, error(&message)),
+    }
+}
+
+/// Returns the board given by the `fen` and `variant` members of `request`.
+fn board(request: &Value) -> Result<Board, (u16, String)> {
+    let rules: &dyn Variant = match request.get("variant").and_then(Value::as_str) {
+        Some(name) => variant::by_name(name).ok_or((400, format!("unknown variant '{name}'")))?,
+        None => &variant::Standard,
+    };
+    match request.get("fen").and_then(Value::as_str) {
+        Some(fen) => {
+            Board::from_fen_variant(fen, rules).map_err(|e| (400, format!("invalid FEN: {e}")))
+        }
+        None => Ok(Board::new_variant(rules)),
+    }
+}
+
+fn legal_moves(request: &Value) -> Result<Value, (u16, String)> {
+    let board = board(request)?;
+    let moves = board
+        .legal_moves()
+        .into_iter()
+        .map(|m| {
+            Value::object([
+                ("uci", m.to_string().into()),
+                ("san", board.san(m).ok().into()),
+            ])
+        })
+        .collect::<Vec<_>>();
+    Ok(Value::object([("moves", moves.into())]))
+}
+
+fn play(request: &Value) -> Result<Value, (u16, String)> {
+    let mut board = board(request)?;
+    let text = request
+        .get("move")
+        .and_then(Value::as_str)
+        .ok_or((400, "missing move".to_string()))?;
+    let movement = match text.parse::<Move>() {
+        Ok(m) => m,
+        Err(_) => board
+            .parse_san(text)
+            .map_err(|e| (422, format!("illegal movement '{text}': {e}")))?,
+    };
+    let san = board
+        .san(movement)
+        .map_err(|e| (422, format!("illegal movement '{text}': {e}")))?;
+    board
+        .make_move(movement)
+        .map_err(|e| (422, format!("illegal movement '{text}': {e}")))?;
+
+    let status = board.status();
+    Ok(Value::object([
+        ("fen", board.to_fen().into()),
+        ("san", san.into()),
+        ("check", board.in_check().into()),
+        ("status", status.name().into()),
+        ("result", status.result().into()),
+        (
+            "reason",
+            status.is_over().then(|| status.to_string()).into(),
+        ),
+    ]))
+}
+
+fn analyze(request: &Value) -> Result<Value, (u16, String)> {
+    let board = board(request)?;
+    let number = |key: &str| match request.get(key) {
+        None => Ok(None),
+        Some(Value::Number(n)) if n.is_finite() && *n > 0.0 => Ok(Some(*n)),
+        Some(_) => Err((400, format!("invalid {key}"))),
+    };
+    let depth = number("depth")?.map(|d| d as u32);
+    // A deep search is stopped after the longest time as well
+    let time = match number("time")? {
+        Some(t) => Duration::from_secs_f64(t.min(MAX_ANALYZE_TIME.as_secs_f64())),
+        None if depth.is_some() => MAX_ANALYZE_TIME,
+        None => ANALYZE_TIME,
+    };
+    let limits = Limits {
+        depth,
+        time: Some(time),
+        ..Limits::default()
+    };
+    let lines = match number("lines")? {
+        None => 1,
+        Some(n) if n >= 1.0 && n.fract() == 0.0 => n as usize,
+        Some(_) => return Err((400, "invalid lines".to_string())),
+    };
+
+    let infos = search::search_lines(&board, &limits, &AtomicBool::new(false), lines, |_| {});
+    let Some(first) = infos.first() else {
+        return Err((422, format!("no legal moves: {}", board.status())));
+    };
+    let line = |info: &Info| {
+        let score = match info.score.for_white(board.current_move()) {
+            Score::Centipawns(cp) => Value::object([("cp", i64::from(cp).into())]),
+            Score::Mate(n) => Value::object([("mate", i64::from(n).into())]),
+        };
+        let pv = info
+            .pv
+            .iter()
+            .map(|m| m.to_string().into())
+            .collect::<Vec<_>>();
+        Value::object([
+            ("score", score),
+            ("pv", pv.into()),
+            ("san", board.san_line(&info.pv).ok().into()),
+        ])
+    };
+    Ok(Value::object([
+        ("best", first.best_move().map(|m| m.to_string()).into()),
+        ("depth", u64::from(first.depth).into()),
+        ("nodes", first.nodes.into()),
+        ("lines", infos.iter().map(line).collect::<Vec<_>>().into()),
+    ]))
+}
+
+fn validate_pgn(request: &Value) -> Result<Value, (u16, String)> {
+    let text = request
+        .get("pgn")
+        .and_then(Value::as_str)
+        .ok_or((400, "missing pgn".to_string()))?;
+    let games = match pgn::parse(text) {
+        Ok(games) => games,
+        Err(e) => {
+            return Ok(Value::object([
+                ("valid", false.into()),
+                ("error", e.to_string().into()),
+            ]))
+        }
+    };
+    let mut valid = true;
+    let games = games
+        .iter()
+        .map(|game| {
+            let error = game.replay().err().map(|e| e.to_string());
+            valid &= error.is_none();
+            Value::object([
+                ("line", (game.line as u64).into()),
+                ("moves", (game.moves.len() as u64).into()),
+                ("result", game.result.as_str().into()),
+                ("error", error.into()),
+            ])
+        })
+        .collect::<Vec<_>>();
+    Ok(Value::object([
+        ("valid", valid.into()),
+        ("games", games.into()),
+    ]))
+}
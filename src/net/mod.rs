@@ -0,0 +1,192 @@
+//! Games between two players on different machines over TCP. One instance hosts the game
+//! and offers the sides, the time control and the starting position, the other joins and
+//! accepts the offer. Then both send their movements as lines like `move e2e4`, and each
+//! checks the movements of the other on its own board. Many games at once are played
+//! through the game server of `server`, and `http` offers the rules to other programs.
+
+pub mod http;
+pub mod json;
+pub mod server;
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use crate::core::board::Board;
+use crate::core::movement::Move;
+use crate::core::piece::Side;
+use crate::core::variant;
+use crate::ui::game::TimeControl;
+
+/// Port used by `host` and `join` when none is given
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// Game proposed by the host
+pub struct Offer {
+    /// Side played by the guest
+    pub side: Side,
+    pub time: Option<TimeControl>,
+    /// Starting position, with the variant it is played by
+    pub board: Board,
+}
+
+/// A line of the protocol
+pub enum Message {
+    /// The game proposed by the host, sent first as
+    /// `offer <white|black> <minutes+increment|-> <variant> <FEN>`
+    Offer(Offer),
+    /// The guest plays the offered game, `accept`
+    Accept,
+    /// The guest doesn't play the offered game, `decline`
+    Decline,
+    /// A movement in UCI notation, `move e2e4`
+    Move(Move),
+    /// The player has left the game, `quit`, also received when the connection is closed
+    Quit,
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::Offer(offer) => {
+                let side = side_name(offer.side);
+                let time = offer.time.map_or("-".to_string(), |tc| tc.to_string());
+                // Variant names may contain spaces, which `variant::by_name` ignores
+                let rules = offer.board.variant().name().replace(' ', "");
+                write!(f, "offer {side} {time} {rules} {}", offer.board.to_fen())
+            }
+            Message::Accept => write!(f, "accept"),
+            Message::Decline => write!(f, "decline"),
+            Message::Move(m) => write!(f, "move {m}"),
+            Message::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+impl Message {
+    /// Parses a line of the protocol. Returns `None` if it isn't a valid message.
+    fn parse(line: &str) -> Option<Message> {
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "offer" => {
+                let mut words = rest.splitn(4, ' ');
+                let side = parse_side(words.next()?)?;
+                let time = match words.next()? {
+                    "-" => None,
+                    tc => Some(tc.parse().ok()?),
+                };
+                let rules = variant::by_name(words.next()?)?;
+                let board = Board::from_fen_variant(words.next()?, rules).ok()?;
+                Some(Message::Offer(Offer { side, time, board }))
+            }
+            "accept" => Some(Message::Accept),
+            "decline" => Some(Message::Decline),
+            "move" => rest.parse().ok().map(Message::Move),
+            "quit" => Some(Message::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Connection to the other player, exchanging `Message`s
+///
+/// # Example
+///
+/// ```
+/// use std::net::TcpListener;
+/// use std::thread;
+///
+/// use rust_chess::net::{Connection, Message};
+///
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let address = listener.local_addr().unwrap();
+/// let guest = thread::spawn(move || {
+///     let mut guest = Connection::connect(address).unwrap();
+///     guest.send(&Message::Move("e2e4".parse().unwrap())).unwrap();
+/// });
+///
+/// let mut host = Connection::accept(&listener).unwrap();
+/// assert!(matches!(host.receive().unwrap(), Message::Move(m) if m.to_string() == "e2e4"));
+/// guest.join().unwrap();
+/// // The guest has closed the connection
+/// assert!(matches!(host.receive().unwrap(), Message::Quit));
+/// ```
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    /// Waits for the other player to connect to `listener`.
+    pub fn accept(listener: &TcpListener) -> io::Result<Connection> {
+        let (stream, _) = listener.accept()?;
+        Connection::new(stream)
+    }
+
+    /// Connects to the player hosting the game at `address`.
+    pub fn connect(address: impl ToSocketAddrs) -> io::Result<Connection> {
+        Connection::new(TcpStream::connect(address)?)
+    }
+
+    fn new(stream: TcpStream) -> io::Result<Connection> {
+        // Every message is a single short line, which should be sent at once
+        stream.set_nodelay(true)?;
+        Ok(Connection {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        writeln!(self.writer, "{message}")
+    }
+
+    /// Waits for the next message. A closed connection is received as `Message::Quit`,
+    /// an invalid message is an error of the kind `InvalidData`, like an offer with a time
+    /// control longer than a day.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::{ErrorKind, Write};
+    /// use std::net::{TcpListener, TcpStream};
+    ///
+    /// use rust_chess::net::Connection;
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let mut host = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    /// let mut guest = Connection::accept(&listener).unwrap();
+    /// let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    /// writeln!(host, "offer white 5+18446744073709551615 standard {fen}").unwrap();
+    /// assert_eq!(guest.receive().err().unwrap().kind(), ErrorKind::InvalidData);
+    /// ```
+    pub fn receive(&mut self) -> io::Result<Message> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(Message::Quit);
+        }
+        let line = line.trim();
+        Message::parse(line).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid message '{line}'"),
+            )
+        })
+    }
+}
+
+/// Returns the name of `side` in the messages, `white` or `black`.
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::White => "white",
+        Side::Black => "black",
+    }
+}
+
+fn parse_side(name: &str) -> Option<Side> {
+    match name {
+        "white" => Some(Side::White),
+        "black" => Some(Side::Black),
+        _ => None,
+    }
+}
@@ -0,0 +1,525 @@
+//! Server hosting many games at once. Clients connect over TCP and exchange JSON
+//! objects, one per line, so they can be written in any language.
+//!
+//! Every request has a `type` and gets answered with an `error` if it fails:
+//!
+//! - `{"type": "create", "variant": "atomic", "fen": "...", "side": "white", "name": "Ann"}`
+//!   creates a game, all members besides `type` are optional. The side defaults to
+//!   `random`. Answered with `created`, like `joined` below, and the `state`.
+//! - `{"type": "join", "game": 1, "name": "Bob"}` takes the free side of a game.
+//!   Answered with `{"type": "joined", "game": 1, "side": "black", "token": "..."}`.
+//! - `{"type": "rejoin", "game": 1, "token": "..."}` takes the side back after
+//!   reconnecting, with the token received when taking it. Answered with `joined`.
+//! - `{"type": "watch", "game": 1}` follows a game as a spectator.
+//! - `{"type": "move", "game": 1, "move": "e2e4"}` plays a movement in UCI notation.
+//! - `{"type": "resign", "game": 1}` gives up the game.
+//! - `{"type": "list"}` is answered with
+//!   `{"type": "games", "games": [{"game": 1, "variant": "Standard", "white": "Ann",
+//!   "black": null, "status": "waiting"}]}`, `null` marking a free side.
+//!
+//! The players and spectators of a game are sent:
+//!
+//! - `{"type": "state", "game": 1, "variant": "Standard", "start": "<FEN>", "moves":
+//!   ["e2e4"], "fen": "<FEN>", "turn": "black", "white": {"name": "Ann", "connected":
+//!   true}, "black": null, "status": "waiting", "result": "*", "reason": null}` when
+//!   they start following the game and whenever a player joins, leaves or returns.
+//!   The status is `waiting` for a second player, `playing` or `over`. Replaying
+//!   `moves` from `start` restores the game.
+//! - `{"type": "move", "game": 1, "move": "e2e4", "san": "e4", "fen": "<FEN>"}` after
+//!   every movement.
+//! - `{"type": "end", "game": 1, "result": "1-0", "reason": "checkmate, White wins"}`
+//!   when the game is over. Finished games are appended to the PGN archive.
+
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::OpenOptions;
+use std::hash::BuildHasher;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::core::board::Board;
+use crate::core::pgn::PgnGame;
+use crate::core::piece::Side;
+use crate::core::variant::{self, Variant};
+
+use super::json::Value;
+use super::{parse_side, side_name};
+
+/// Number identifying a connection
+type ClientId = u64;
+
+/// A side of a game
+#[derive(Default)]
+struct Seat {
+    name: String,
+    /// Proves the side belongs to a client when it reconnects, `None` while the side is free
+    token: Option<String>,
+    /// Connection of the player, `None` while disconnected
+    client: Option<ClientId>,
+}
+
+struct ServerGame {
+    board: Board,
+    /// Indexed by side
+    seats: [Seat; 2],
+    spectators: Vec<ClientId>,
+    /// Side which has given up the game
+    resigned: Option<Side>,
+}
+
+impl ServerGame {
+    fn is_full(&self) -> bool {
+        self.seats.iter().all(|s| s.token.is_some())
+    }
+
+    fn is_over(&self) -> bool {
+        self.resigned.is_some() || self.board.status().is_over()
+    }
+
+    fn status(&self) -> &'static str {
+        if self.is_over() {
+            "over"
+        } else if self.is_full() {
+            "playing"
+        } else {
+            "waiting"
+        }
+    }
+
+    /// Returns the result as written in PGN and its reason if the game is over.
+    fn result(&self) -> (&'static str, Option<String>) {
+        match self.resigned {
+            Some(Side::White) => ("0-1", Some("White resigned".to_string())),
+            Some(Side::Black) => ("1-0", Some("Black resigned".to_string())),
+            None => {
+                let status = self.board.status();
+                (
+                    status.result(),
+                    status.is_over().then(|| status.to_string()),
+                )
+            }
+        }
+    }
+
+    /// Returns the clients of the players and the spectators.
+    fn clients(&self) -> impl Iterator<Item = ClientId> + '_ {
+        self.seats
+            .iter()
+            .filter_map(|s| s.client)
+            .chain(self.spectators.iter().copied())
+    }
+
+    /// Returns the side played by `client`.
+    fn side_of(&self, client: ClientId) -> Option<Side> {
+        [Side::White, Side::Black]
+            .into_iter()
+            .find(|&side| self.seats[side as usize].client == Some(client))
+    }
+}
+
+/// All games of the server and the connected clients
+struct Lobby {
+    games: BTreeMap<u64, ServerGame>,
+    clients: HashMap<ClientId, Sender<String>>,
+    /// PGN file finished games are appended to
+    archive: PathBuf,
+    /// Seeded randomly, used to choose sides and generate tokens
+    random: RandomState,
+    /// Number of random values drawn from `random`
+    drawn: u64,
+}
+
+/// Accepts clients on `listener`, serving each one on its own thread. Finished games
+/// are appended to the PGN file `archive`.
+pub fn run(listener: TcpListener, archive: PathBuf) {
+    let lobby = Arc::new(Mutex::new(Lobby {
+        games: BTreeMap::new(),
+        clients: HashMap::new(),
+        archive,
+        random: RandomState::new(),
+        drawn: 0,
+    }));
+    for (id, stream) in (1..).zip(listener.incoming()) {
+        // A client may have given up before its connection was accepted
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let lobby = Arc::clone(&lobby);
+        thread::spawn(move || serve(stream, id, &lobby));
+    }
+}
+
+/// Answers the requests of the client `id` until it disconnects.
+fn serve(stream: TcpStream, id: ClientId, lobby: &Mutex<Lobby>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    // Messages are written on their own thread, so that a slow client doesn't block
+    // the others. It ends when the client is removed from the lobby.
+    let (sender, receiver) = mpsc::channel::<String>();
+    thread::spawn(move || {
+        for line in receiver {
+            if writeln!(writer, "{line}").is_err() {
+                break;
+            }
+        }
+    });
+    lobby.lock().unwrap().clients.insert(id, sender);
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut lobby = lobby.lock().unwrap();
+        match Value::parse(&line) {
+            Ok(request) => lobby.handle(id, &request),
+            Err(e) => lobby.send(id, &error(&format!("invalid JSON: {e}"))),
+        }
+    }
+    lobby.lock().unwrap().disconnect(id);
+}
+
+fn error(message: &str) -> Value {
+    Value::object([("type", "error".into()), ("message", message.into())])
+}
+
+impl Lobby {
+    fn send(&self, client: ClientId, message: &Value) {
+        if let Some(sender) = self.clients.get(&client) {
+            // The client may be disconnecting
+            let _ = sender.send(message.to_string());
+        }
+    }
+
+    /// Sends `message` to the players and spectators of the game `id`.
+    fn broadcast(&self, id: u64, message: &Value) {
+        for client in self.games[&id].clients() {
+            self.send(client, message);
+        }
+    }
+
+    fn handle(&mut self, client: ClientId, request: &Value) {
+        let game = request.get("game").and_then(Value::as_u64);
+        let result = match request.get("type").and_then(Value::as_str) {
+            Some("list") => {
+                self.list(client);
+                Ok(())
+            }
+            Some("create") => self.create(client, request),
+            Some(kind @ ("join" | "rejoin" | "watch" | "move" | "resign")) => match game {
+                Some(id) if self.games.contains_key(&id) => match kind {
+                    "join" => self.join(client, id, request),
+                    "rejoin" => self.rejoin(client, id, request),
+                    "watch" => {
+                        self.watch(client, id);
+                        Ok(())
+                    }
+                    "move" => self.play(client, id, request),
+                    _ => self.resign(client, id),
+                },
+                Some(id) => Err(format!("no game {id}")),
+                None => Err("missing game".to_string()),
+            },
+            Some(kind) => Err(format!("unknown request type '{kind}'")),
+            None => Err("missing request type".to_string()),
+        };
+        if let Err(message) = result {
+            self.send(client, &error(&message));
+        }
+    }
+
+    fn list(&self, client: ClientId) {
+        let games = self
+            .games
+            .iter()
+            .map(|(&id, game)| {
+                let name = |side: Side| {
+                    let seat = &game.seats[side as usize];
+                    seat.token.as_ref().map(|_| seat.name.clone())
+                };
+                Value::object([
+                    ("game", id.into()),
+                    ("variant", game.board.variant().name().into()),
+                    ("white", name(Side::White).into()),
+                    ("black", name(Side::Black).into()),
+                    ("status", game.status().into()),
+                ])
+            })
+            .collect::<Vec<_>>();
+        self.send(
+            client,
+            &Value::object([("type", "games".into()), ("games", games.into())]),
+        );
+    }
+
+    fn create(&mut self, client: ClientId, request: &Value) -> Result<(), String> {
+        let rules: &dyn Variant = match request.get("variant").and_then(Value::as_str) {
+            Some(name) => variant::by_name(name).ok_or(format!("unknown variant '{name}'"))?,
+            None => &variant::Standard,
+        };
+        let board = match request.get("fen").and_then(Value::as_str) {
+            Some(fen) => {
+                Board::from_fen_variant(fen, rules).map_err(|e| format!("invalid FEN: {e}"))?
+            }
+            None => Board::new_variant(rules),
+        };
+        let side = match request.get("side").and_then(Value::as_str) {
+            None | Some("random") => {
+                if self.random().is_multiple_of(2) {
+                    Side::White
+                } else {
+                    Side::Black
+                }
+            }
+            Some(side) => parse_side(side).ok_or(format!("invalid side '{side}'"))?,
+        };
+
+        let id = self.games.last_key_value().map_or(1, |(id, _)| id + 1);
+        self.games.insert(
+            id,
+            ServerGame {
+                board,
+                seats: Default::default(),
+                spectators: Vec::new(),
+                resigned: None,
+            },
+        );
+        self.take_seat(client, id, side, request, "created");
+        self.send_state(client, id);
+        Ok(())
+    }
+
+    fn join(&mut self, client: ClientId, id: u64, request: &Value) -> Result<(), String> {
+        let game = &self.games[&id];
+        if game.side_of(client).is_some() {
+            return Err("you already play in this game".to_string());
+        }
+        let side = [Side::White, Side::Black]
+            .into_iter()
+            .find(|&side| game.seats[side as usize].token.is_none())
+            .ok_or("the game is full, watch it instead")?;
+        self.take_seat(client, id, side, request, "joined");
+        self.broadcast_state(id);
+        Ok(())
+    }
+
+    /// Gives `side` of the game `id` to `client` and sends it the token of the side.
+    fn take_seat(&mut self, client: ClientId, id: u64, side: Side, request: &Value, answer: &str) {
+        let token = format!("{:016x}", self.random());
+        let name = request.get("name").and_then(Value::as_str).unwrap_or("?");
+        let game = self.games.get_mut(&id).expect("the game exists");
+        game.seats[side as usize] = Seat {
+            name: name.to_string(),
+            token: Some(token.clone()),
+            client: Some(client),
+        };
+        game.spectators.retain(|&c| c != client);
+        self.send(
+            client,
+            &Value::object([
+                ("type", answer.into()),
+                ("game", id.into()),
+                ("side", side_name(side).into()),
+                ("token", token.into()),
+            ]),
+        );
+    }
+
+    fn rejoin(&mut self, client: ClientId, id: u64, request: &Value) -> Result<(), String> {
+        let token = request
+            .get("token")
+            .and_then(Value::as_str)
+            .ok_or("missing token")?;
+        let game = self.games.get_mut(&id).expect("the game exists");
+        let side = [Side::White, Side::Black]
+            .into_iter()
+            .find(|&side| game.seats[side as usize].token.as_deref() == Some(token))
+            .ok_or("invalid token")?;
+        game.seats[side as usize].client = Some(client);
+        game.spectators.retain(|&c| c != client);
+        self.send(
+            client,
+            &Value::object([
+                ("type", "joined".into()),
+                ("game", id.into()),
+                ("side", side_name(side).into()),
+                ("token", token.into()),
+            ]),
+        );
+        self.broadcast_state(id);
+        Ok(())
+    }
+
+    fn watch(&mut self, client: ClientId, id: u64) {
+        let game = self.games.get_mut(&id).expect("the game exists");
+        if !game.clients().any(|c| c == client) {
+            game.spectators.push(client);
+        }
+        self.send_state(client, id);
+    }
+
+    fn play(&mut self, client: ClientId, id: u64, request: &Value) -> Result<(), String> {
+        let text = request
+            .get("move")
+            .and_then(Value::as_str)
+            .ok_or("missing move")?;
+        let game = self.games.get_mut(&id).expect("the game exists");
+        if game.is_over() {
+            return Err("the game is over".to_string());
+        }
+        if !game.is_full() {
+            return Err("waiting for the second player".to_string());
+        }
+        if game.side_of(client) != Some(game.board.current_move()) {
+            return Err("not your turn".to_string());
+        }
+        let movement = text
+            .parse()
+            .map_err(|_| format!("invalid movement '{text}'"))?;
+        let san = game
+            .board
+            .san(movement)
+            .map_err(|e| format!("illegal movement '{text}': {e}"))?;
+        game.board
+            .make_move(movement)
+            .map_err(|e| format!("illegal movement '{text}': {e}"))?;
+
+        let message = Value::object([
+            ("type", "move".into()),
+            ("game", id.into()),
+            ("move", movement.to_string().into()),
+            ("san", san.into()),
+            ("fen", game.board.to_fen().into()),
+        ]);
+        self.broadcast(id, &message);
+        if self.games[&id].is_over() {
+            self.finish(id);
+        }
+        Ok(())
+    }
+
+    fn resign(&mut self, client: ClientId, id: u64) -> Result<(), String> {
+        let game = self.games.get_mut(&id).expect("the game exists");
+        let side = game.side_of(client).ok_or("you don't play this game")?;
+        if game.is_over() {
+            return Err("the game is over".to_string());
+        }
+        if !game.is_full() {
+            return Err("waiting for the second player".to_string());
+        }
+        game.resigned = Some(side);
+        self.finish(id);
+        Ok(())
+    }
+
+    /// Announces the result of the game `id` and archives it.
+    fn finish(&self, id: u64) {
+        let game = &self.games[&id];
+        let (result, reason) = game.result();
+        self.broadcast(
+            id,
+            &Value::object([
+                ("type", "end".into()),
+                ("game", id.into()),
+                ("result", result.into()),
+                ("reason", reason.clone().into()),
+            ]),
+        );
+
+        let mut pgn = PgnGame::from_board(&game.board);
+        pgn.set_tag("Event", "rust-chess server");
+        pgn.set_tag("Round", &id.to_string());
+        pgn.set_tag("White", &game.seats[Side::White as usize].name);
+        pgn.set_tag("Black", &game.seats[Side::Black as usize].name);
+        pgn.set_tag("Result", result);
+        if let Some(reason) = reason {
+            pgn.set_tag("Termination", &reason);
+        }
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.archive)
+            .and_then(|mut file| writeln!(file, "{pgn}"));
+        if let Err(e) = written {
+            eprintln!(
+                "Archiving game {id} to '{}' failed: {e}",
+                self.archive.display()
+            );
+        }
+    }
+
+    /// Removes `client` from all games, which keep its sides for a `rejoin`.
+    fn disconnect(&mut self, client: ClientId) {
+        self.clients.remove(&client);
+        let mut left = Vec::new();
+        for (&id, game) in &mut self.games {
+            game.spectators.retain(|&c| c != client);
+            for seat in &mut game.seats {
+                if seat.client == Some(client) {
+                    seat.client = None;
+                    left.push(id);
+                }
+            }
+        }
+        for id in left {
+            self.broadcast_state(id);
+        }
+    }
+
+    fn send_state(&self, client: ClientId, id: u64) {
+        self.send(client, &self.state(id));
+    }
+
+    fn broadcast_state(&self, id: u64) {
+        self.broadcast(id, &self.state(id));
+    }
+
+    fn state(&self, id: u64) -> Value {
+        let game = &self.games[&id];
+        let start = game.board.start_position();
+        let moves = game
+            .board
+            .moves_played()
+            .iter()
+            .map(|m| m.to_string().into())
+            .collect::<Vec<_>>();
+        let player = |side: Side| {
+            let seat = &game.seats[side as usize];
+            match seat.token {
+                Some(_) => Value::object([
+                    ("name", seat.name.as_str().into()),
+                    ("connected", seat.client.is_some().into()),
+                ]),
+                None => Value::Null,
+            }
+        };
+        let (result, reason) = game.result();
+        Value::object([
+            ("type", "state".into()),
+            ("game", id.into()),
+            ("variant", game.board.variant().name().into()),
+            ("start", start.to_fen().into()),
+            ("moves", moves.into()),
+            ("fen", game.board.to_fen().into()),
+            ("turn", side_name(game.board.current_move()).into()),
+            ("white", player(Side::White)),
+            ("black", player(Side::Black)),
+            ("status", game.status().into()),
+            ("result", result.into()),
+            ("reason", reason.into()),
+        ])
+    }
+
+    /// Returns a new random number.
+    fn random(&mut self) -> u64 {
+        self.drawn += 1;
+        self.random.hash_one(self.drawn)
+    }
+}
@@ -0,0 +1,247 @@
+//! The Universal Chess Interface (UCI), used by graphical interfaces to talk to engines.
+
+use std::io::{self, BufRead};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::core::board::Board;
+use crate::core::movement::Move;
+use crate::core::piece::Side;
+use crate::core::variant::{self, Variant};
+use crate::engine::book::Book;
+use crate::engine::search::{self, Info, Limits, Score};
+
+/// Search running in the background, stopped by setting `stop`
+struct Running {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+/// Stops the running search, if any, and waits until it has sent its best movement.
+fn stop(running: &mut Option<Running>) {
+    if let Some(r) = running.take() {
+        r.stop.store(true, Ordering::Relaxed);
+        let _ = r.handle.join();
+    }
+}
+
+/// Reads UCI commands from stdin and answers on stdout until `quit` is received
+/// or the input ends.
+pub fn run() -> io::Result<()> {
+    let mut board = Board::new();
+    let mut chess960 = false;
+    let mut rules: &dyn Variant = &variant::Standard;
+    let mut running: Option<Running> = None;
+    // Opening book set with `BookFile`, used with `OwnBook`
+    let mut book: Option<Book> = None;
+    let mut own_book = false;
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            continue;
+        };
+
+        match command {
+            "uci" => {
+                println!("id name rust-chess {}", env!("CARGO_PKG_VERSION"));
+                println!("id author lysolaka");
+                println!("option name UCI_Chess960 type check default false");
+                println!("option name OwnBook type check default false");
+                println!("option name BookFile type string default <empty>");
+                let names: Vec<String> = variant::VARIANTS
+                    .iter()
+                    .map(|v| format!("var {}", uci_name(*v)))
+                    .collect();
+                println!(
+                    "option name UCI_Variant type combo default {} {}",
+                    uci_name(&variant::Standard),
+                    names.join(" ")
+                );
+                println!("uciok");
+            }
+            "isready" => println!("readyok"),
+            "ucinewgame" => {
+                stop(&mut running);
+                board = Board::new_variant(rules);
+            }
+            "position" => {
+                stop(&mut running);
+                match position(words, rules, chess960) {
+                    Ok(b) => board = b,
+                    Err(e) => println!("info string {e}"),
+                }
+            }
+            "go" => {
+                stop(&mut running);
+                let (limits, infinite) = go(&board, words);
+                // An infinite search is analysis, which the book doesn't help with
+                let from_book = book
+                    .as_ref()
+                    .filter(|_| own_book && !infinite)
+                    .and_then(|book| book.pick(&board));
+                match from_book {
+                    Some(m) => println!("bestmove {m}"),
+                    None => running = Some(start(board.clone(), limits, infinite)),
+                }
+            }
+            "stop" => stop(&mut running),
+            "setoption" => match option(words) {
+                Some((name, value)) if name == "UCI_Chess960" => chess960 = value == "true",
+                Some((name, value)) if name == "OwnBook" => own_book = value == "true",
+                Some((name, value)) if name == "BookFile" => {
+                    book = None;
+                    if !value.is_empty() && value != "<empty>" {
+                        match Book::open(Path::new(&value)) {
+                            Ok(b) => book = Some(b),
+                            Err(e) => println!("info string reading the book failed: {e}"),
+                        }
+                    }
+                }
+                Some((name, value)) if name == "UCI_Variant" => match variant::by_name(&value) {
+                    Some(v) => rules = v,
+                    None => println!("info string unknown variant '{value}'"),
+                },
+                Some((name, _)) => println!("info string unknown option '{name}'"),
+                None => println!("info string expected 'setoption name <id> [value <x>]'"),
+            },
+            "quit" => break,
+            // Debug mode and registration aren't needed
+            "debug" | "register" | "ponderhit" => {}
+            _ => println!("info string unknown command '{command}'"),
+        }
+    }
+
+    stop(&mut running);
+    Ok(())
+}
+
+/// Parses the arguments of `setoption`: `name <id> value <x>` into the name and the value.
+/// Both may contain spaces, the value is empty if it is missing.
+fn option<'a>(mut words: impl Iterator<Item = &'a str>) -> Option<(String, String)> {
+    if words.next() != Some("name") {
+        return None;
+    }
+    let name: Vec<&str> = words.by_ref().take_while(|&w| w != "value").collect();
+    let value: Vec<&str> = words.collect();
+    Some((name.join(" "), value.join(" ")))
+}
+
+/// Returns the name of `rules` in the `UCI_Variant` option, like `kingofthehill`.
+fn uci_name(rules: &dyn Variant) -> String {
+    rules.name().to_lowercase().replace([' ', '-'], "")
+}
+
+/// Parses the arguments of `position`: `startpos` or `fen <FEN>`, optionally followed
+/// by `moves` and the movements performed since, played by `rules`. With `chess960`
+/// castling is written as the king capturing its own rook.
+fn position<'a>(
+    mut words: impl Iterator<Item = &'a str>,
+    rules: &'static dyn Variant,
+    chess960: bool,
+) -> Result<Board, String> {
+    let mut board = match words.next() {
+        Some("startpos") => {
+            if let Some(word) = words.next() {
+                if word != "moves" {
+                    return Err(format!("unexpected '{word}' after 'startpos'"));
+                }
+            }
+            Board::new_variant(rules)
+        }
+        Some("fen") => {
+            let fen: Vec<&str> = words.by_ref().take_while(|&w| w != "moves").collect();
+            Board::from_fen_variant(&fen.join(" "), rules)
+                .map_err(|e| format!("invalid FEN: {e}"))?
+        }
+        _ => return Err("expected 'startpos' or 'fen'".to_string()),
+    };
+    if chess960 {
+        board.set_chess960(true);
+    }
+
+    for word in words {
+        let m: Move = word
+            .parse()
+            .map_err(|e| format!("invalid movement '{word}': {e}"))?;
+        board
+            .make_move(m)
+            .map_err(|e| format!("illegal movement '{word}': {e}"))?;
+    }
+    Ok(board)
+}
+
+/// Parses the arguments of `go` into the limits of the search. Also returns true
+/// for `infinite`, where the best movement may only be sent after `stop`.
+fn go<'a>(board: &Board, mut words: impl Iterator<Item = &'a str>) -> (Limits, bool) {
+    let mut limits = Limits::default();
+    let mut infinite = false;
+    let mut time = [None; 2];
+    let mut increment = [Duration::ZERO; 2];
+    let mut moves_to_go = None;
+
+    let millis = |w: Option<&str>| w.and_then(|w| w.parse().ok()).map(Duration::from_millis);
+    while let Some(word) = words.next() {
+        match word {
+            "depth" => limits.depth = words.next().and_then(|w| w.parse().ok()),
+            "nodes" => limits.nodes = words.next().and_then(|w| w.parse().ok()),
+            "movetime" => limits.time = millis(words.next()),
+            "wtime" => time[Side::White as usize] = millis(words.next()),
+            "btime" => time[Side::Black as usize] = millis(words.next()),
+            "winc" => increment[Side::White as usize] = millis(words.next()).unwrap_or_default(),
+            "binc" => increment[Side::Black as usize] = millis(words.next()).unwrap_or_default(),
+            "movestogo" => moves_to_go = words.next().and_then(|w| w.parse().ok()),
+            "infinite" => infinite = true,
+            _ => {}
+        }
+    }
+
+    let side = board.current_move() as usize;
+    if let (Some(remaining), None) = (time[side], limits.time) {
+        limits.time = Limits::from_clock(remaining, increment[side], moves_to_go).time;
+    }
+    (limits, infinite)
+}
+
+/// Starts searching `board` in the background. The best movement is sent when the search
+/// ends, or after `stop` for an infinite search.
+fn start(board: Board, limits: Limits, infinite: bool) -> Running {
+    let stop = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&stop);
+
+    let handle = thread::spawn(move || {
+        let best = search::search(&board, &limits, &flag, |info| {
+            println!("{}", info_line(info))
+        });
+        while infinite && !flag.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        match best.and_then(|info| info.best_move()) {
+            Some(m) => println!("bestmove {m}"),
+            // UCI requires an answer even without legal movements
+            None => println!("bestmove 0000"),
+        }
+    });
+    Running { stop, handle }
+}
+
+fn info_line(info: &Info) -> String {
+    let score = match info.score {
+        Score::Centipawns(cp) => format!("cp {cp}"),
+        Score::Mate(n) => format!("mate {n}"),
+    };
+    let pv: Vec<String> = info.pv.iter().map(Move::to_string).collect();
+    format!(
+        "info depth {} seldepth {} score {score} nodes {} nps {} time {} pv {}",
+        info.depth,
+        info.seldepth,
+        info.nodes,
+        info.nps(),
+        info.time.as_millis(),
+        pv.join(" ")
+    )
+}
@@ -0,0 +1,369 @@
+//! The Chess Engine Communication Protocol (CECP) version 2, used by XBoard, WinBoard and
+//! other interfaces to talk to engines.
+
+use std::io::{self, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::core::board::Board;
+use crate::core::movement::Move;
+use crate::core::piece::Side;
+use crate::core::variant::{self, Variant};
+use crate::engine::search::{self, Info, Limits, Score};
+
+/// Time per movement when the interface sets neither a time control nor clocks
+const DEFAULT_MOVE_TIME: Duration = Duration::from_secs(5);
+/// Longest time accepted by `level`, `st`, `time` and `otim`
+const MAX_TIME: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Variants with their names in CECP, besides `fischerandom` which is standard chess
+/// with Chess960 castling. Antichess is closest to the `giveaway` of XBoard.
+const VARIANTS: [(&str, &dyn Variant); 7] = [
+    ("normal", &variant::Standard),
+    ("kingofthehill", &variant::KingOfTheHill),
+    ("3check", &variant::ThreeCheck),
+    ("giveaway", &variant::Antichess),
+    ("horde", &variant::Horde),
+    ("crazyhouse", &variant::Crazyhouse),
+    ("atomic", &variant::Atomic),
+];
+
+/// Search running in the background, stopped by setting `stop`
+struct Running {
+    stop: Arc<AtomicBool>,
+    /// Set when the movement found shouldn't be played anymore
+    cancelled: Arc<Mutex<bool>>,
+    /// Returns the movement the engine has played, if any
+    handle: JoinHandle<Option<Move>>,
+}
+
+/// State of the engine between the commands
+struct Session {
+    board: Board,
+    /// Side played by the engine, `None` in force mode
+    engine: Option<Side>,
+    /// Send the thinking output while searching
+    post: bool,
+    /// Movements per time control, 0 if the whole game has to be played in it
+    moves_per_control: u32,
+    increment: Duration,
+    /// Remaining time on the clock of the engine
+    time: Option<Duration>,
+    /// Fixed time per movement set by `st`
+    move_time: Option<Duration>,
+    /// Maximal depth set by `sd`
+    depth: Option<u32>,
+    running: Option<Running>,
+}
+
+/// Reads CECP commands from stdin and answers on stdout until `quit` is received
+/// or the input ends.
+pub fn run() -> io::Result<()> {
+    let mut session = Session {
+        board: Board::new(),
+        engine: Some(Side::Black),
+        post: false,
+        moves_per_control: 0,
+        increment: Duration::ZERO,
+        time: None,
+        move_time: None,
+        depth: None,
+        running: None,
+    };
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            continue;
+        };
+        let argument = line[command.len()..].trim();
+
+        match command {
+            "protover" => {
+                let names: Vec<&str> = VARIANTS.iter().map(|(name, _)| *name).collect();
+                println!(
+                    "feature myname=\"rust-chess {}\" setboard=1 usermove=1 ping=1 playother=1 \
+                     san=0 colors=0 time=1 draw=0 analyze=0 sigint=0 sigterm=0 reuse=1 \
+                     variants=\"{},fischerandom\"",
+                    env!("CARGO_PKG_VERSION"),
+                    names.join(",")
+                );
+                println!("feature done=1");
+            }
+            "new" => {
+                session.finish(true);
+                session.board = Board::new();
+                session.engine = Some(Side::Black);
+                session.depth = None;
+            }
+            "variant" => {
+                session.finish(true);
+                match argument {
+                    "fischerandom" => {
+                        session.board = Board::new();
+                        session.board.set_chess960(true);
+                    }
+                    name => match VARIANTS.iter().find(|(n, _)| *n == name) {
+                        Some((_, rules)) => session.board = Board::new_variant(*rules),
+                        None => println!("Error (unknown variant): {name}"),
+                    },
+                }
+            }
+            "setboard" => {
+                session.finish(true);
+                let chess960 = session.board.is_chess960();
+                match Board::from_fen_variant(argument, session.board.variant()) {
+                    Ok(mut board) => {
+                        if chess960 {
+                            board.set_chess960(true);
+                        }
+                        session.board = board;
+                    }
+                    Err(e) => println!("tellusererror Illegal position: {e}"),
+                }
+            }
+            "usermove" => {
+                session.finish(true);
+                match parse_move(&session.board, argument) {
+                    Ok(m) => {
+                        session.board.make_move_unchecked(m);
+                        if session.engine == Some(session.board.current_move()) {
+                            session.think();
+                        }
+                    }
+                    Err(e) => println!("Illegal move ({e}): {argument}"),
+                }
+            }
+            "go" => {
+                session.finish(true);
+                session.engine = Some(session.board.current_move());
+                session.think();
+            }
+            "playother" => {
+                session.finish(true);
+                session.engine = Some(session.board.current_move().opposite());
+            }
+            "force" | "result" => {
+                session.finish(true);
+                session.engine = None;
+            }
+            // Move now
+            "?" => session.finish(false),
+            "undo" | "remove" => {
+                session.finish(true);
+                let count = if command == "undo" { 1 } else { 2 };
+                for _ in 0..count {
+                    session.board.unmake_move();
+                }
+            }
+            "level" => match level(argument) {
+                Some((moves, base, increment)) => {
+                    session.moves_per_control = moves;
+                    session.time = Some(base);
+                    session.increment = increment;
+                    session.move_time = None;
+                }
+                None => println!("Error (invalid time control): {argument}"),
+            },
+            "st" => match seconds(argument).filter(|time| !time.is_zero()) {
+                Some(time) => session.move_time = Some(time),
+                None => println!("Error (invalid time): {argument}"),
+            },
+            "sd" => match argument.parse() {
+                Ok(depth) => session.depth = Some(depth),
+                Err(_) => println!("Error (invalid depth): {argument}"),
+            },
+            "time" => match clock_time(argument) {
+                Some(time) => session.time = Some(time),
+                None => println!("Error (invalid time): {argument}"),
+            },
+            // The clock of the opponent doesn't change the search
+            "otim" => {
+                if clock_time(argument).is_none() {
+                    println!("Error (invalid time): {argument}");
+                }
+            }
+            "post" => session.post = true,
+            "nopost" => session.post = false,
+            "ping" => println!("pong {argument}"),
+            "quit" => break,
+            // Commands which don't change anything for this engine
+            "xboard" | "accepted" | "rejected" | "easy" | "hard" | "random" | "computer"
+            | "name" | "rating" | "ics" | "draw" | "hint" | "bk" | "white" | "black" => {}
+            _ => println!("Error (unknown command): {command}"),
+        }
+    }
+
+    session.finish(true);
+    Ok(())
+}
+
+impl Session {
+    /// Starts searching the movement of the engine in the background. The movement is
+    /// sent when the search ends and performed on `board` by the next `finish`.
+    fn think(&mut self) {
+        if self.board.status().is_over() {
+            return;
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let cancelled = Arc::new(Mutex::new(false));
+        let board = self.board.clone();
+        let limits = self.limits();
+        let post = self.post;
+
+        let handle = thread::spawn({
+            let stop = Arc::clone(&stop);
+            let cancelled = Arc::clone(&cancelled);
+            move || {
+                let best = search::search(&board, &limits, &stop, |info| {
+                    if post {
+                        println!("{}", thinking(&board, info));
+                    }
+                });
+                // Holding the lock, so that the movement is either sent and returned
+                // or neither
+                let cancelled = cancelled.lock().unwrap();
+                let m = best
+                    .and_then(|info| info.best_move())
+                    .filter(|_| !*cancelled)?;
+                println!("move {}", notation(&board, m));
+                let mut after = board.clone();
+                after.make_move_unchecked(m);
+                let status = after.status();
+                if status.is_over() {
+                    println!("{} {{{status}}}", status.result());
+                }
+                Some(m)
+            }
+        });
+        self.running = Some(Running {
+            stop,
+            cancelled,
+            handle,
+        });
+    }
+
+    /// Stops the running search, if any, and performs the movement the engine has sent.
+    /// With `cancel` a movement which hasn't been sent yet is dropped.
+    fn finish(&mut self, cancel: bool) {
+        if let Some(r) = self.running.take() {
+            if cancel {
+                *r.cancelled.lock().unwrap() = true;
+            }
+            r.stop.store(true, Ordering::Relaxed);
+            if let Ok(Some(m)) = r.handle.join() {
+                self.board.make_move_unchecked(m);
+            }
+        }
+    }
+
+    /// Returns the limits of the next search from the time control and the clock.
+    fn limits(&self) -> Limits {
+        let moves_to_go = (self.moves_per_control > 0).then(|| {
+            let played = self.board.fullmove_number().saturating_sub(1);
+            self.moves_per_control - played % self.moves_per_control
+        });
+        let mut limits = match (self.move_time, self.time) {
+            (Some(time), _) => Limits::time(time),
+            (None, Some(remaining)) => Limits::from_clock(remaining, self.increment, moves_to_go),
+            (None, None) => Limits::time(DEFAULT_MOVE_TIME),
+        };
+        limits.depth = self.depth;
+        limits
+    }
+}
+
+/// Parses the movement sent by `usermove`, written like `e2e4`, `e7e8q` or `P@e4`,
+/// or as `O-O` and `O-O-O` when castling in Chess960.
+fn parse_move(board: &Board, word: &str) -> Result<Move, &'static str> {
+    let m = match word.parse() {
+        Ok(m) => m,
+        Err(_) => board.parse_san(word)?,
+    };
+    if board.legal_moves().contains(&m) {
+        Ok(m)
+    } else {
+        Err("not a legal movement")
+    }
+}
+
+/// Writes the movement `m` of the engine. Castling in Chess960 is written as `O-O`
+/// or `O-O-O`, as XBoard expects, other movements like `e2e4`.
+fn notation(board: &Board, m: Move) -> String {
+    match board.san(m) {
+        Ok(san) if board.is_chess960() && san.starts_with("O-O") => {
+            san.trim_end_matches(['+', '#']).to_string()
+        }
+        _ => m.to_string(),
+    }
+}
+
+/// Parses the arguments of `level`: the number of movements per time control, the base
+/// time in minutes, optionally with seconds like `2:30`, and the increment in seconds.
+/// Both times may be a day at most.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use rust_chess::protocol::xboard::level;
+///
+/// let (moves, base, increment) = level("40 2:30 1.5").unwrap();
+/// assert_eq!((moves, base), (40, Duration::from_secs(150)));
+/// assert_eq!(increment, Duration::from_millis(1500));
+///
+/// assert!(level("40 5 1e19").is_none());
+/// assert!(level("0 307445734561825861 0").is_none());
+/// ```
+pub fn level(argument: &str) -> Option<(u32, Duration, Duration)> {
+    let mut words = argument.split_whitespace();
+    let moves = words.next()?.parse().ok()?;
+    let base = words.next()?;
+    let (minutes, secs) = base.split_once(':').unwrap_or((base, "0"));
+    let base = minutes
+        .parse::<u64>()
+        .ok()?
+        .checked_mul(60)?
+        .checked_add(secs.parse().ok()?)?;
+    let base = Some(Duration::from_secs(base)).filter(|&base| base <= MAX_TIME)?;
+    let increment = seconds(words.next()?)?;
+    if words.next().is_some() {
+        return None;
+    }
+    Some((moves, base, increment))
+}
+
+/// Parses a number of seconds like `1.5`, up to `MAX_TIME`.
+fn seconds(text: &str) -> Option<Duration> {
+    let time = Duration::try_from_secs_f64(text.parse().ok()?).ok()?;
+    (time <= MAX_TIME).then_some(time)
+}
+
+/// Parses the time on a clock in centiseconds, as given by `time` and `otim`. It may
+/// be negative if the engine has overstepped it, which counts as no time left.
+fn clock_time(text: &str) -> Option<Duration> {
+    let centiseconds = text.parse::<i64>().ok()?.max(0).unsigned_abs();
+    let time = Duration::from_millis(centiseconds.checked_mul(10)?);
+    (time <= MAX_TIME).then_some(time)
+}
+
+/// Returns the thinking output for `info`: the depth, the score in centipawns from the
+/// engine's perspective, the time in centiseconds, the number of nodes and the line in SAN.
+/// A mate in `n` movements scores 100000 + `n`, being mated -100000 - `n`.
+fn thinking(board: &Board, info: &Info) -> String {
+    let score = match info.score {
+        Score::Centipawns(cp) => cp,
+        Score::Mate(n) if n > 0 => 100_000 + n,
+        Score::Mate(n) => -100_000 + n,
+    };
+    format!(
+        "{} {score} {} {} {}",
+        info.depth,
+        info.time.as_millis() / 10,
+        info.nodes,
+        board.san_line(&info.pv).unwrap_or_default()
+    )
+}
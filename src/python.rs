@@ -0,0 +1,317 @@
+//! Bindings for Python, built with the `python` feature as the extension module
+//! `rust_chess`, for example with `maturin develop --release` or `maturin build
+//! --release` using `pyproject.toml`. Movements are written in UCI notation like `e2e4`,
+//! `e7e8q` or `P@e4`, or given as `Move` objects.
+//!
+//! ```python
+//! import rust_chess
+//!
+//! board = rust_chess.Board()
+//! board.push("e4")
+//! print(board.fen(), len(board.legal_moves()), board.perft(3), board.evaluate())
+//! games = rust_chess.read_pgn(open("games.pgn").read())
+//! ```
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::core::board::Board;
+use crate::core::movement::Move;
+use crate::core::pgn::{self, PgnGame};
+use crate::core::piece::{Side, Type};
+use crate::core::variant::{self, Variant};
+use crate::engine::eval;
+
+fn value_error(reason: impl ToString) -> PyErr {
+    PyValueError::new_err(reason.to_string())
+}
+
+/// A movement of a piece, or a drop of a piece from the hand
+#[pyclass(name = "Move", module = "rust_chess", frozen, eq, hash, from_py_object)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct PyMove(Move);
+
+#[pymethods]
+impl PyMove {
+    /// Reads a movement in UCI notation, like `e2e4`, `e7e8q` or `P@e4`.
+    #[new]
+    fn new(uci: &str) -> PyResult<Self> {
+        uci.parse().map(PyMove).map_err(value_error)
+    }
+
+    /// Field the piece leaves, like `e2`, the same as `to_square` for a drop
+    #[getter(from_square)]
+    fn start(&self) -> String {
+        self.0.start.to_string()
+    }
+
+    /// Field the piece ends on, like `e4`
+    #[getter(to_square)]
+    fn end(&self) -> String {
+        self.0.end.to_string()
+    }
+
+    /// Letter of the piece a pawn becomes, like `Q`, or `None`
+    #[getter]
+    fn promotion(&self) -> Option<char> {
+        self.0.promotion.as_ref().map(Type::letter)
+    }
+
+    /// Letter of the piece dropped from the hand, like `N`, or `None`
+    #[getter]
+    fn drop(&self) -> Option<char> {
+        self.0.drop.as_ref().map(Type::letter)
+    }
+
+    /// Returns the movement in UCI notation.
+    fn uci(&self) -> String {
+        self.0.to_string()
+    }
+
+    /// Returns the movement in UCI notation, like `e2e4`.
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    /// Returns the movement as the expression constructing it, like `Move('e2e4')`.
+    fn __repr__(&self) -> String {
+        format!("Move('{}')", self.0)
+    }
+}
+
+/// A movement given to a method, as a `Move` or as text
+#[derive(FromPyObject)]
+enum MoveArg {
+    Move(PyMove),
+    Text(String),
+}
+
+/// A position of chess or one of its variants, with the movements leading to it
+#[pyclass(name = "Board", module = "rust_chess", skip_from_py_object)]
+#[derive(Clone)]
+struct PyBoard {
+    board: Board,
+}
+
+impl PyBoard {
+    /// Returns the movement, reading text in UCI notation or SAN.
+    fn movement(&self, movement: MoveArg) -> PyResult<Move> {
+        match movement {
+            MoveArg::Move(m) => Ok(m.0),
+            MoveArg::Text(text) => match text.parse() {
+                Ok(m) => Ok(m),
+                Err(_) => self.board.parse_san(&text).map_err(value_error),
+            },
+        }
+    }
+}
+
+#[pymethods]
+impl PyBoard {
+    /// Constructs the board of the position `fen`, or of the starting position if it
+    /// isn't given, played by the rules of `variant`, like `"atomic"`, or of standard
+    /// chess.
+    #[new]
+    #[pyo3(signature = (fen=None, variant=None))]
+    fn new(fen: Option<&str>, variant: Option<&str>) -> PyResult<Self> {
+        let rules: &dyn Variant = match variant {
+            Some(name) => variant::by_name(name)
+                .ok_or_else(|| value_error(format!("unknown variant '{name}'")))?,
+            None => &variant::Standard,
+        };
+        let board = match fen {
+            Some(fen) => Board::from_fen_variant(fen, rules).map_err(value_error)?,
+            None => Board::new_variant(rules),
+        };
+        Ok(PyBoard { board })
+    }
+
+    /// Returns the position in Forsyth-Edwards Notation.
+    fn fen(&self) -> String {
+        self.board.to_fen()
+    }
+
+    /// Side to move, `"white"` or `"black"`
+    #[getter]
+    fn turn(&self) -> &'static str {
+        match self.board.current_move() {
+            Side::White => "white",
+            Side::Black => "black",
+        }
+    }
+
+    /// Movements performed since the starting position
+    #[getter]
+    fn moves(&self) -> Vec<PyMove> {
+        self.board.moves_played().into_iter().map(PyMove).collect()
+    }
+
+    /// Returns the legal movements of the side to move.
+    fn legal_moves(&self) -> Vec<PyMove> {
+        self.board.legal_moves().into_iter().map(PyMove).collect()
+    }
+
+    /// Returns whether `movement`, a `Move` or text in UCI notation or SAN, is legal.
+    fn is_legal(&self, movement: MoveArg) -> bool {
+        self.movement(movement)
+            .is_ok_and(|m| self.board.legal_moves().contains(&m))
+    }
+
+    /// Performs `movement`, a `Move` or text in UCI notation or SAN, and returns it
+    /// in SAN. Raises `ValueError` if it isn't legal.
+    fn push(&mut self, movement: MoveArg) -> PyResult<String> {
+        let m = self.movement(movement)?;
+        let san = self.board.san(m).map_err(value_error)?;
+        self.board.make_move(m).map_err(value_error)?;
+        Ok(san)
+    }
+
+    /// Takes back the last movement and returns it, or `None` if there was none.
+    fn pop(&mut self) -> Option<PyMove> {
+        self.board.unmake_move().map(PyMove)
+    }
+
+    /// Returns the legal movement `movement` in SAN, like `Nf3`.
+    fn san(&self, movement: PyMove) -> PyResult<String> {
+        self.board.san(movement.0).map_err(value_error)
+    }
+
+    /// Reads a legal movement written in SAN.
+    fn parse_san(&self, san: &str) -> PyResult<PyMove> {
+        self.board.parse_san(san).map(PyMove).map_err(value_error)
+    }
+
+    /// Returns whether the king of the side to move is attacked.
+    fn is_check(&self) -> bool {
+        self.board.in_check()
+    }
+
+    /// Returns whether the game has ended, see `status` for how.
+    fn is_game_over(&self) -> bool {
+        self.board.status().is_over()
+    }
+
+    /// Returns whether the game goes on or how it has ended: `ongoing`, `checkmate`,
+    /// `stalemate`, `fifty-move-rule`, `repetition`, `insufficient-material` or
+    /// `variant-win`.
+    fn status(&self) -> &'static str {
+        self.board.status().name()
+    }
+
+    /// Returns the result as written in PGN: `1-0`, `0-1`, `1/2-1/2` or `*`.
+    fn result(&self) -> &'static str {
+        self.board.status().result()
+    }
+
+    /// Counts the leaf nodes of the tree of legal movements `depth` half-moves deep.
+    /// Other Python threads keep running meanwhile.
+    fn perft(&self, py: Python<'_>, depth: u32) -> u64 {
+        py.detach(|| self.board.perft(depth))
+    }
+
+    /// Like `perft`, but returns the number of leaf nodes after every legal movement.
+    fn perft_divide(&self, py: Python<'_>, depth: u32) -> Vec<(PyMove, u64)> {
+        py.detach(|| self.board.perft_divide(depth))
+            .into_iter()
+            .map(|(m, nodes)| (PyMove(m), nodes))
+            .collect()
+    }
+
+    /// Evaluates the position statically in centipawns from White's perspective.
+    fn evaluate(&self) -> i32 {
+        eval::evaluate(&self.board)
+    }
+
+    /// Returns the game leading to the position in PGN.
+    fn pgn(&self) -> String {
+        PgnGame::from_board(&self.board).to_string()
+    }
+
+    /// Returns an independent board with the same position and movements.
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    /// Returns the position in Forsyth-Edwards Notation, like `fen`.
+    fn __str__(&self) -> String {
+        self.board.to_fen()
+    }
+
+    /// Returns the board as the expression constructing it, like `Board('<fen>')`.
+    fn __repr__(&self) -> String {
+        format!("Board('{}')", self.board.to_fen())
+    }
+}
+
+/// A game read from PGN: its tags, its movements in SAN and its result
+#[pyclass(name = "PgnGame", module = "rust_chess", frozen)]
+struct PyPgnGame {
+    game: PgnGame,
+}
+
+#[pymethods]
+impl PyPgnGame {
+    /// Tag pairs in the order of the PGN text, like `("White", "Carlsen, Magnus")`
+    #[getter]
+    fn tags(&self) -> Vec<(String, String)> {
+        self.game.tags.clone()
+    }
+
+    /// Movements of the main line in SAN
+    #[getter]
+    fn moves(&self) -> Vec<String> {
+        self.game.moves.clone()
+    }
+
+    /// Result from the end of the movement text, like `1-0`
+    #[getter]
+    fn result(&self) -> String {
+        self.game.result.clone()
+    }
+
+    /// Line of the PGN text where the game starts
+    #[getter]
+    fn line(&self) -> usize {
+        self.game.line
+    }
+
+    /// Returns the value of the tag `name`, or `None` if it isn't present.
+    fn tag(&self, name: &str) -> Option<String> {
+        self.game.tag(name).map(str::to_string)
+    }
+
+    /// Returns the board the game starts from.
+    fn start(&self) -> PyResult<PyBoard> {
+        let board = self.game.start().map_err(value_error)?;
+        Ok(PyBoard { board })
+    }
+
+    /// Returns the board after all movements of the game. Raises `ValueError` if one
+    /// of them is illegal.
+    fn board(&self) -> PyResult<PyBoard> {
+        let board = self.game.replay().map_err(value_error)?;
+        Ok(PyBoard { board })
+    }
+
+    /// Returns the game in PGN.
+    fn __str__(&self) -> String {
+        self.game.to_string()
+    }
+}
+
+/// Reads the games of a PGN text. Raises `ValueError` if it can't be read.
+#[pyfunction]
+fn read_pgn(text: &str) -> PyResult<Vec<PyPgnGame>> {
+    let games = pgn::parse(text).map_err(value_error)?;
+    Ok(games.into_iter().map(|game| PyPgnGame { game }).collect())
+}
+
+/// Rules and engine of rust-chess
+#[pymodule]
+fn rust_chess(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyBoard>()?;
+    m.add_class::<PyMove>()?;
+    m.add_class::<PyPgnGame>()?;
+    m.add_function(wrap_pyfunction!(read_pgn, m)?)?;
+    Ok(())
+}
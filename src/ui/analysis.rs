@@ -0,0 +1,28 @@
+use std::sync::atomic::AtomicBool;
+
+use crate::core::board::Board;
+use crate::engine::search::{self, Info, Limits};
+
+/// Searches `board` until one of the `limits` is reached or `stop` is set and prints
+/// the best `lines` lines in SAN after every completed depth, with scores from White's
+/// perspective. Returns the lines of the last completed depth, best first.
+pub fn analyze(board: &Board, limits: &Limits, lines: usize, stop: &AtomicBool) -> Vec<Info> {
+    let report = |infos: &[Info]| {
+        for (i, info) in infos.iter().enumerate() {
+            let line = if lines > 1 {
+                format!("line {}  ", i + 1)
+            } else {
+                String::new()
+            };
+            let pv = board.san_line(&info.pv).unwrap_or_default();
+            println!(
+                "depth {:>2}  {line}score {:>6}  nodes {:>9}  time {:>6} ms  {pv}",
+                info.depth,
+                info.score.for_white(board.current_move()).to_string(),
+                info.nodes,
+                info.time.as_millis()
+            );
+        }
+    };
+    search::search_lines(board, limits, stop, lines, report)
+}
@@ -0,0 +1,675 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::core::board::Board;
+use crate::core::piece::Side;
+use crate::core::variant::{self, Variant};
+use crate::engine::search::Limits;
+use crate::net;
+
+use super::error::UsageError;
+use super::game::{Settings, TimeControl};
+use super::render::GlyphSet;
+
+/// Time `analyze` searches when neither a depth nor a time is given
+const ANALYZE_TIME: Duration = Duration::from_secs(5);
+
+pub const USAGE: &str = "\
+Usage: rust-chess [SUBCOMMAND] [OPTIONS]
+
+Subcommands:
+  play            play a game in the terminal (the default)
+  perft           count the positions reachable in a number of moves
+  analyze         search the best lines in a position
+  pgn validate    check the syntax and the moves of a PGN file
+  book build      build a Polyglot opening book from PGN files
+  uci             talk the Universal Chess Interface protocol on stdin/stdout
+  xboard          talk the XBoard/WinBoard protocol (CECP) on stdin/stdout,
+                  also started with '--xboard'
+  host            wait for another player to join a game over the network
+  join            join a game hosted by another player
+  serve           run a server hosting many games for network clients
+  api             answer questions about positions over HTTP
+  help            show this help, or the help of a subcommand
+
+Run 'rust-chess SUBCOMMAND --help' for the options of a subcommand.";
+
+const PLAY_USAGE: &str = "\
+Usage: rust-chess play [OPTIONS]
+
+Options:
+  --fen FEN                      start from the given position
+  --variant NAME                 play by the rules of a variant: standard,
+                                 kingofthehill, threecheck, antichess, horde,
+                                 crazyhouse or atomic
+  --chess960 NUMBER              start from the Chess960 position NUMBER (0-959),
+                                 518 is the standard one
+  --white human|engine           who plays White (default: human)
+  --black human|engine           who plays Black (default: human)
+  --time MINUTES+INCREMENT       play with clocks, for example '5+3'
+  --resume FILE                  continue a game saved with 'save FILE',
+                                 with its players and clocks
+  --book FILE                    let the engine play from a Polyglot opening
+                                 book (.bin) while it has moves
+  --glyphs solid|outline|ascii   glyphs used to draw the pieces
+  --ascii                        shorthand for '--glyphs ascii'
+  --no-color                     draw without colours
+  --coach                        warn before hanging a piece, allowing mate in
+                                 one or missing it (not in the TUI)
+  --tui                          use the full-screen front-end (feature 'tui')";
+
+const PERFT_USAGE: &str = "\
+Usage: rust-chess perft DEPTH [OPTIONS]
+
+Counts the positions reachable in DEPTH half-moves.
+
+Options:
+  --fen FEN         start from the given position instead of the starting position
+  --variant NAME    use the rules of a variant, like 'horde'
+  --divide          also show the count after every legal movement";
+
+const ANALYZE_USAGE: &str = "\
+Usage: rust-chess analyze FEN [OPTIONS]
+
+Searches the position and prints the best lines found at every depth.
+Searches 5 seconds if neither '--depth' nor '--time' is given,
+pressing Enter stops the search earlier.
+
+Options:
+  --depth N         search N half-moves deep
+  --time SECONDS    search for at most SECONDS
+  --lines N         show the N best lines starting with different moves
+                    (default: 1)
+  --variant NAME    use the rules of a variant, like 'kingofthehill'";
+
+const PGN_USAGE: &str = "\
+Usage: rust-chess pgn validate FILE
+
+Reads all games of FILE and replays their movements.
+Exits with 1 if any game is invalid.";
+
+const BOOK_USAGE: &str = "\
+Usage: rust-chess book build PATH [OPTIONS]
+
+Builds a Polyglot opening book from the games of the PGN file PATH, or of all
+'.pgn' files in the folder PATH. A move is weighted by the results of the games
+it was played in: 2 points for a win, 1 for a draw or an unknown result ('*')
+and none for a loss. Games of variants other than standard chess (including
+Chess960) are skipped.
+
+Options:
+  --output FILE     write the book to FILE (default: book.bin)
+  --max-ply N       only use the first N half-moves of every game (default: 30)
+  --min-games N     only keep moves played in at least N games (default: 1)";
+
+const UCI_USAGE: &str = "\
+Usage: rust-chess uci
+
+Runs the engine with the Universal Chess Interface protocol, for graphical interfaces.";
+
+const XBOARD_USAGE: &str = "\
+Usage: rust-chess xboard
+       rust-chess --xboard
+
+Runs the engine with the Chess Engine Communication Protocol (CECP, version 2)
+of XBoard and WinBoard, for graphical interfaces.";
+
+const HOST_USAGE: &str = "\
+Usage: rust-chess host [OPTIONS]
+
+Waits for another player to join with 'rust-chess join' and offers them a game.
+
+Options:
+  --port PORT                    listen on PORT (default: 7878)
+  --side white|black|random      side played by the host (default: random)
+  --time MINUTES+INCREMENT       play with clocks, for example '5+3'
+  --fen FEN                      start from the given position
+  --variant NAME                 play by the rules of a variant, like 'atomic'
+  --glyphs solid|outline|ascii   glyphs used to draw the pieces
+  --ascii                        shorthand for '--glyphs ascii'
+  --no-color                     draw without colours
+  --coach                        warn before hanging a piece, allowing mate in
+                                 one or missing it";
+
+const JOIN_USAGE: &str = "\
+Usage: rust-chess join HOST[:PORT] [OPTIONS]
+
+Joins the game offered by 'rust-chess host' at HOST, on port 7878 if none is given.
+
+Options:
+  --glyphs solid|outline|ascii   glyphs used to draw the pieces
+  --ascii                        shorthand for '--glyphs ascii'
+  --no-color                     draw without colours
+  --coach                        warn before hanging a piece, allowing mate in
+                                 one or missing it";
+
+const SERVE_USAGE: &str = "\
+Usage: rust-chess serve [OPTIONS]
+
+Runs a server hosting many games at once, with players and spectators
+connecting over TCP and sending JSON objects, one per line.
+
+Options:
+  --port PORT       listen on PORT (default: 7878)
+  --archive FILE    append finished games to the PGN file FILE
+                    (default: archive.pgn)";
+
+const API_USAGE: &str = "\
+Usage: rust-chess api [OPTIONS]
+
+Runs a local HTTP service taking and returning JSON: POST /legal-moves,
+/move, /analyze and /pgn/validate.
+
+Options:
+  --port PORT       listen on PORT (default: 8080)";
+
+/// Subcommand given on the command line, with its options
+pub enum Subcommand {
+    Play {
+        board: Board,
+        settings: Settings,
+        /// Saved game to continue instead of starting at `board` with `settings`
+        resume: Option<PathBuf>,
+        /// Polyglot opening book for the engine
+        book: Option<PathBuf>,
+        glyphs: Option<GlyphSet>,
+        no_color: bool,
+        tui: bool,
+        /// Warn human players about blunders before performing their movements
+        coach: bool,
+    },
+    Perft {
+        board: Board,
+        depth: u32,
+        divide: bool,
+    },
+    Analyze {
+        board: Board,
+        limits: Limits,
+        /// Number of lines to show (multi-PV)
+        lines: usize,
+    },
+    PgnValidate {
+        file: PathBuf,
+    },
+    BookBuild {
+        /// PGN file, or folder of PGN files
+        input: PathBuf,
+        output: PathBuf,
+        max_ply: usize,
+        min_games: u32,
+    },
+    Uci,
+    Xboard,
+    Host {
+        board: Board,
+        port: u16,
+        /// Side played by the host, chosen at random if `None`
+        side: Option<Side>,
+        time: Option<TimeControl>,
+        glyphs: Option<GlyphSet>,
+        no_color: bool,
+        coach: bool,
+    },
+    Join {
+        /// Host and port of the game
+        address: String,
+        glyphs: Option<GlyphSet>,
+        no_color: bool,
+        coach: bool,
+    },
+    Serve {
+        port: u16,
+        /// PGN file finished games are appended to
+        archive: PathBuf,
+    },
+    Api {
+        port: u16,
+    },
+    /// Show the help of the subcommand, or the general help
+    Help(Option<&'static str>),
+}
+
+/// Returns the help text of `subcommand`, or the general help for `None`.
+pub fn help(subcommand: Option<&str>) -> &'static str {
+    match subcommand {
+        Some("play") => PLAY_USAGE,
+        Some("perft") => PERFT_USAGE,
+        Some("analyze") => ANALYZE_USAGE,
+        Some("pgn") => PGN_USAGE,
+        Some("book") => BOOK_USAGE,
+        Some("uci") => UCI_USAGE,
+        Some("xboard") => XBOARD_USAGE,
+        Some("host") => HOST_USAGE,
+        Some("join") => JOIN_USAGE,
+        Some("serve") => SERVE_USAGE,
+        Some("api") => API_USAGE,
+        _ => USAGE,
+    }
+}
+
+/// Parses the command-line arguments, without the program name.
+/// Without a subcommand the arguments are the options of `play`.
+///
+/// # Example
+///
+/// ```
+/// use rust_chess::ui::cli::{parse, Subcommand};
+///
+/// let args = ["perft", "3", "--divide"].map(String::from);
+/// assert!(matches!(parse(args), Ok(Subcommand::Perft { depth: 3, divide: true, .. })));
+///
+/// assert!(parse(["perft".to_string()]).is_err());
+/// ```
+pub fn parse<I>(args: I) -> Result<Subcommand, UsageError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter().peekable();
+    let subcommand = match args.peek().map(String::as_str) {
+        Some("help") => {
+            args.next();
+            return Ok(Subcommand::Help(args.next().and_then(|t| known(&t))));
+        }
+        Some("--help" | "-h") => return Ok(Subcommand::Help(None)),
+        Some("--xboard") => "xboard",
+        Some(arg) if !arg.starts_with('-') => known(arg).ok_or_else(|| UsageError {
+            reason: format!("unknown subcommand '{arg}'"),
+            subcommand: None,
+        })?,
+        _ => return parse_play(args),
+    };
+    args.next();
+
+    if args.peek().is_some_and(|a| a == "--help" || a == "-h") {
+        return Ok(Subcommand::Help(Some(subcommand)));
+    }
+    if subcommand == "play" {
+        return parse_play(args);
+    }
+    let mut args = Args { args, subcommand };
+
+    match subcommand {
+        "perft" => {
+            let depth = args.positional("DEPTH")?;
+            let depth = depth
+                .parse()
+                .map_err(|_| args.error(format!("invalid depth '{depth}'")))?;
+            let mut fen = None;
+            let mut variant: &dyn Variant = &variant::Standard;
+            let mut divide = false;
+            while let Some(arg) = args.next_option()? {
+                match arg.as_str() {
+                    "--fen" => fen = Some(args.value(&arg)?),
+                    "--variant" => variant = args.variant(&arg)?,
+                    "--divide" => divide = true,
+                    _ => return Err(args.unknown(&arg)),
+                }
+            }
+            Ok(Subcommand::Perft {
+                board: args.board(fen, variant)?,
+                depth,
+                divide,
+            })
+        }
+        "analyze" => {
+            // The FEN may be given quoted or as separate arguments
+            let mut fen = args.positional("FEN")?;
+            while let Some(part) = args.args.next_if(|a| !a.starts_with("--")) {
+                fen.push(' ');
+                fen.push_str(&part);
+            }
+            let mut variant: &dyn Variant = &variant::Standard;
+            let mut limits = Limits::default();
+            let mut lines = 1;
+            while let Some(arg) = args.next_option()? {
+                match arg.as_str() {
+                    "--depth" => {
+                        let depth = args.value(&arg)?;
+                        limits.depth = Some(
+                            depth
+                                .parse()
+                                .map_err(|_| args.error(format!("invalid depth '{depth}'")))?,
+                        );
+                    }
+                    "--time" => limits.time = Some(args.seconds(&arg)?),
+                    "--lines" => {
+                        let n = args.value(&arg)?;
+                        lines =
+                            n.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+                                args.error(format!("invalid number of lines '{n}'"))
+                            })?;
+                    }
+                    "--variant" => variant = args.variant(&arg)?,
+                    _ => return Err(args.unknown(&arg)),
+                }
+            }
+            if limits.depth.is_none() && limits.time.is_none() {
+                limits.time = Some(ANALYZE_TIME);
+            }
+            Ok(Subcommand::Analyze {
+                board: args.board(Some(fen), variant)?,
+                limits,
+                lines,
+            })
+        }
+        "pgn" => {
+            let action = args.positional("'validate'")?;
+            if action != "validate" {
+                return Err(args.error(format!("unknown pgn action '{action}'")));
+            }
+            let file = args.positional("FILE")?;
+            if let Some(arg) = args.next_option()? {
+                return Err(args.unknown(&arg));
+            }
+            Ok(Subcommand::PgnValidate { file: file.into() })
+        }
+        "book" => {
+            let action = args.positional("'build'")?;
+            if action != "build" {
+                return Err(args.error(format!("unknown book action '{action}'")));
+            }
+            let input = args.positional("PATH")?;
+            let mut output = PathBuf::from("book.bin");
+            let mut max_ply = 30;
+            let mut min_games = 1;
+            while let Some(arg) = args.next_option()? {
+                match arg.as_str() {
+                    "--output" => output = PathBuf::from(args.value(&arg)?),
+                    "--max-ply" => {
+                        let n = args.value(&arg)?;
+                        max_ply = n.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+                            args.error(format!("invalid number of half-moves '{n}'"))
+                        })?;
+                    }
+                    "--min-games" => {
+                        let n = args.value(&arg)?;
+                        min_games =
+                            n.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+                                args.error(format!("invalid number of games '{n}'"))
+                            })?;
+                    }
+                    _ => return Err(args.unknown(&arg)),
+                }
+            }
+            Ok(Subcommand::BookBuild {
+                input: input.into(),
+                output,
+                max_ply,
+                min_games,
+            })
+        }
+        "host" => {
+            let mut fen = None;
+            let mut variant: &dyn Variant = &variant::Standard;
+            let mut port = net::DEFAULT_PORT;
+            let mut side = None;
+            let mut time = None;
+            let mut glyphs = None;
+            let mut no_color = false;
+            let mut coach = false;
+            while let Some(arg) = args.next_option()? {
+                match arg.as_str() {
+                    "--port" => port = args.port(&arg)?,
+                    "--side" => {
+                        side = match args.value(&arg)?.as_str() {
+                            "white" => Some(Side::White),
+                            "black" => Some(Side::Black),
+                            "random" => None,
+                            value => return Err(args.error(format!("invalid side '{value}'"))),
+                        }
+                    }
+                    "--time" => time = Some(args.parsed(&arg)?),
+                    "--fen" => fen = Some(args.value(&arg)?),
+                    "--variant" => variant = args.variant(&arg)?,
+                    "--glyphs" => glyphs = Some(args.parsed(&arg)?),
+                    "--ascii" => glyphs = Some(GlyphSet::Ascii),
+                    "--no-color" => no_color = true,
+                    "--coach" => coach = true,
+                    _ => return Err(args.unknown(&arg)),
+                }
+            }
+            Ok(Subcommand::Host {
+                board: args.board(fen, variant)?,
+                port,
+                side,
+                time,
+                glyphs,
+                no_color,
+                coach,
+            })
+        }
+        "join" => {
+            let mut address = args.positional("HOST")?;
+            if !address.contains(':') {
+                address = format!("{address}:{}", net::DEFAULT_PORT);
+            }
+            let mut glyphs = None;
+            let mut no_color = false;
+            let mut coach = false;
+            while let Some(arg) = args.next_option()? {
+                match arg.as_str() {
+                    "--glyphs" => glyphs = Some(args.parsed(&arg)?),
+                    "--ascii" => glyphs = Some(GlyphSet::Ascii),
+                    "--no-color" => no_color = true,
+                    "--coach" => coach = true,
+                    _ => return Err(args.unknown(&arg)),
+                }
+            }
+            Ok(Subcommand::Join {
+                address,
+                glyphs,
+                no_color,
+                coach,
+            })
+        }
+        "serve" => {
+            let mut port = net::DEFAULT_PORT;
+            let mut archive = PathBuf::from("archive.pgn");
+            while let Some(arg) = args.next_option()? {
+                match arg.as_str() {
+                    "--port" => port = args.port(&arg)?,
+                    "--archive" => archive = PathBuf::from(args.value(&arg)?),
+                    _ => return Err(args.unknown(&arg)),
+                }
+            }
+            Ok(Subcommand::Serve { port, archive })
+        }
+        "api" => {
+            let mut port = net::http::DEFAULT_PORT;
+            while let Some(arg) = args.next_option()? {
+                match arg.as_str() {
+                    "--port" => port = args.port(&arg)?,
+                    _ => return Err(args.unknown(&arg)),
+                }
+            }
+            Ok(Subcommand::Api { port })
+        }
+        _ => {
+            if let Some(arg) = args.next_option()? {
+                return Err(args.unknown(&arg));
+            }
+            if subcommand == "xboard" {
+                Ok(Subcommand::Xboard)
+            } else {
+                Ok(Subcommand::Uci)
+            }
+        }
+    }
+}
+
+/// Returns the name of the subcommand `name` if it exists
+fn known(name: &str) -> Option<&'static str> {
+    [
+        "play", "perft", "analyze", "pgn", "book", "uci", "xboard", "host", "join", "serve", "api",
+    ]
+    .into_iter()
+    .find(|&s| s == name)
+}
+
+fn parse_play<I>(args: I) -> Result<Subcommand, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut args = Args {
+        args: args.peekable(),
+        subcommand: "play",
+    };
+    let mut fen = None;
+    let mut variant: &dyn Variant = &variant::Standard;
+    let mut chess960 = None;
+    let mut settings = Settings::default();
+    let mut resume = None;
+    let mut book = None;
+    // Option which sets up a new game, and so conflicts with '--resume'
+    let mut new_game = None;
+    let mut glyphs = None;
+    let mut no_color = false;
+    let mut tui = false;
+    let mut coach = false;
+
+    while let Some(arg) = args.next_option()? {
+        match arg.as_str() {
+            "--help" | "-h" => return Ok(Subcommand::Help(Some("play"))),
+            "--fen" => fen = Some(args.value(&arg)?),
+            "--variant" => variant = args.variant(&arg)?,
+            "--chess960" => {
+                let number = args.value(&arg)?;
+                chess960 = Some(
+                    number
+                        .parse()
+                        .ok()
+                        .and_then(|n| Board::new_chess960(n).ok())
+                        .ok_or_else(|| {
+                            args.error(format!("invalid Chess960 position '{number}'"))
+                        })?,
+                );
+            }
+            "--white" => settings.white = args.parsed(&arg)?,
+            "--black" => settings.black = args.parsed(&arg)?,
+            "--time" => settings.time = Some(args.parsed(&arg)?),
+            "--resume" => resume = Some(PathBuf::from(args.value(&arg)?)),
+            "--book" => book = Some(PathBuf::from(args.value(&arg)?)),
+            "--glyphs" => glyphs = Some(args.parsed(&arg)?),
+            "--ascii" => glyphs = Some(GlyphSet::Ascii),
+            "--no-color" => no_color = true,
+            "--tui" => tui = true,
+            "--coach" => coach = true,
+            _ => return Err(args.unknown(&arg)),
+        }
+        if matches!(
+            arg.as_str(),
+            "--fen" | "--variant" | "--chess960" | "--white" | "--black" | "--time"
+        ) {
+            new_game = Some(arg);
+        }
+    }
+    if let (Some(_), Some(option)) = (&resume, new_game) {
+        return Err(args.error(format!("'--resume' can't be combined with '{option}'")));
+    }
+    let board = match chess960 {
+        Some(_) if fen.is_some() || variant.name() != variant::Standard.name() => {
+            return Err(args
+                .error("'--chess960' can't be combined with '--fen' or '--variant'".to_string()))
+        }
+        Some(board) => board,
+        None => args.board(fen, variant)?,
+    };
+    Ok(Subcommand::Play {
+        board,
+        settings,
+        resume,
+        book,
+        glyphs,
+        no_color,
+        tui,
+        coach,
+    })
+}
+
+/// Remaining arguments of a subcommand
+struct Args<I: Iterator<Item = String>> {
+    args: std::iter::Peekable<I>,
+    subcommand: &'static str,
+}
+
+impl<I: Iterator<Item = String>> Args<I> {
+    fn error(&self, reason: String) -> UsageError {
+        UsageError {
+            reason,
+            subcommand: Some(self.subcommand),
+        }
+    }
+
+    fn unknown(&self, arg: &str) -> UsageError {
+        self.error(format!("unexpected argument '{arg}'"))
+    }
+
+    /// Returns the next argument, which has to be an option
+    fn next_option(&mut self) -> Result<Option<String>, UsageError> {
+        match self.args.next() {
+            Some(arg) if !arg.starts_with("--") && arg != "-h" => Err(self.unknown(&arg)),
+            arg => Ok(arg),
+        }
+    }
+
+    /// Returns the next argument, which has to be a positional argument called `name`
+    fn positional(&mut self, name: &str) -> Result<String, UsageError> {
+        match self.args.next() {
+            Some(arg) if !arg.starts_with("--") => Ok(arg),
+            _ => Err(self.error(format!("missing {name}"))),
+        }
+    }
+
+    /// Returns the value of `option`
+    fn value(&mut self, option: &str) -> Result<String, UsageError> {
+        self.args
+            .next()
+            .ok_or_else(|| self.error(format!("'{option}' requires a value")))
+    }
+
+    fn parsed<T>(&mut self, option: &str) -> Result<T, UsageError>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        let value = self.value(option)?;
+        value.parse().map_err(|e: T::Err| self.error(e.to_string()))
+    }
+
+    fn seconds(&mut self, option: &str) -> Result<Duration, UsageError> {
+        let value = self.value(option)?;
+        value
+            .parse::<f64>()
+            .ok()
+            .filter(|s| *s > 0.0)
+            .and_then(|s| Duration::try_from_secs_f64(s).ok())
+            .ok_or_else(|| self.error(format!("invalid number of seconds '{value}'")))
+    }
+
+    fn port(&mut self, option: &str) -> Result<u16, UsageError> {
+        let value = self.value(option)?;
+        value
+            .parse()
+            .map_err(|_| self.error(format!("invalid port '{value}'")))
+    }
+
+    fn variant(&mut self, option: &str) -> Result<&'static dyn Variant, UsageError> {
+        let name = self.value(option)?;
+        variant::by_name(&name).ok_or_else(|| self.error(format!("unknown variant '{name}'")))
+    }
+
+    /// Returns the board set up from `fen`, or the starting position without it,
+    /// played by the rules of `variant`.
+    fn board(
+        &self,
+        fen: Option<String>,
+        variant: &'static dyn Variant,
+    ) -> Result<Board, UsageError> {
+        match fen {
+            Some(fen) => Board::from_fen_variant(&fen, variant)
+                .map_err(|e| self.error(format!("invalid FEN: {e}"))),
+            None => Ok(Board::new_variant(variant)),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+
+use crate::core::board::Board;
+use crate::core::movement::Move;
+use crate::core::piece::Type;
+use crate::core::position::Pos;
+
+/// A reason to think twice about a movement, found by `review`
+#[derive(Debug, PartialEq)]
+pub enum Warning {
+    /// The side to move could win at once with the movement, written in SAN
+    MissesMate(String),
+    /// The other side can win at once with the movement, written in SAN
+    AllowsMate(String),
+    /// The other side can win material by capturing the piece at the position
+    Hangs(Type, Pos),
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::MissesMate(san) => write!(f, "misses mate in one with {san}"),
+            Warning::AllowsMate(san) => write!(f, "allows mate in one with {san}"),
+            Warning::Hangs(t, pos) => write!(f, "hangs the {} on {pos}", name(*t)),
+        }
+    }
+}
+
+/// Looks for blunders in `movement`, which has to be legal on `board`: missing a win in
+/// one movement, allowing the other side one, or leaving a piece to be captured with
+/// a loss of material, judged by `Board::see`. In variants the wins of the variant count
+/// as mates, and pieces aren't checked where capturing works differently, as in
+/// Antichess and Atomic chess.
+///
+/// # Example
+///
+/// ```
+/// use rust_chess::core::board::Board;
+/// use rust_chess::ui::coach::{review, Warning};
+///
+/// let b = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+///
+/// let warnings = review(&b, "a1a2".parse().unwrap());
+/// assert_eq!(warnings, vec![Warning::MissesMate("Ra8#".to_string())]);
+/// assert!(review(&b, "a1a8".parse().unwrap()).is_empty());
+/// ```
+pub fn review(board: &Board, movement: Move) -> Vec<Warning> {
+    let side = board.current_move();
+    let mut warnings = Vec::new();
+
+    let mut after = board.clone();
+    after.make_move_unchecked(movement);
+    if after.status().winner() == Some(side) {
+        return warnings;
+    }
+
+    if let Some(win) = winning_move(board) {
+        warnings.push(Warning::MissesMate(win));
+    }
+    if let Some(win) = winning_move(&after) {
+        warnings.push(Warning::AllowsMate(win));
+    }
+
+    let variant = board.variant();
+    if variant.royal_king() && !variant.explosions() {
+        let mut gained = match board.at(movement.end) {
+            Some(p) => p.p_type().value(),
+            None if board.is_capture(movement) => Type::Pawn(false).value(),
+            None => 0,
+        };
+        if let Some(t) = movement.promotion {
+            gained += t.value() - Type::Pawn(false).value();
+        }
+        let threat = after
+            .legal_moves()
+            .into_iter()
+            .filter(|&m| after.is_capture(m))
+            .max_by_key(|m| after.see(m));
+        if let Some(threat) = threat.filter(|m| after.see(m) > gained) {
+            let t = after
+                .at(threat.end)
+                .map_or(Type::Pawn(false), |p| p.p_type());
+            warnings.push(Warning::Hangs(t, threat.end));
+        }
+    }
+    warnings
+}
+
+/// Returns a movement, in SAN, with which the side to move wins at once.
+fn winning_move(board: &Board) -> Option<String> {
+    let side = board.current_move();
+    if board.status().is_over() {
+        return None;
+    }
+    board.legal_moves().into_iter().find_map(|m| {
+        let mut after = board.clone();
+        after.make_move_unchecked(m);
+        (after.status().winner() == Some(side)).then(|| board.san(m).unwrap_or(m.to_string()))
+    })
+}
+
+fn name(t: Type) -> &'static str {
+    match t {
+        Type::King => "king",
+        Type::Queen => "queen",
+        Type::Rook => "rook",
+        Type::Bishop => "bishop",
+        Type::Knight => "knight",
+        Type::Pawn(_) => "pawn",
+    }
+}
@@ -1,28 +1,283 @@
-use crate::core::board::Board;
+use std::error::Error;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
 
-use super::input;
+use crate::core::movement::Move;
+use crate::core::piece::{Side, Type};
+use crate::core::position::Pos;
+use crate::engine::search::{self, Limits};
+use crate::net::{Connection, Message};
+
+use super::analysis;
+use super::coach;
 use super::error::QuitGame;
+use super::game::{Game, Player};
+use super::input::{self, Command};
+use super::render::Renderer;
+
+/// Plays `game` in the terminal until it is over or the player quits. With `coach`
+/// the movements of human players are checked for blunders before they are performed.
+/// The movements of a `Player::Remote` are received from `remote`, which is sent
+/// the movements made here.
+pub fn game_loop(
+    game: &mut Game,
+    renderer: &dyn Renderer,
+    mut coach: bool,
+    mut remote: Option<&mut Connection>,
+) {
+    let mut highlights: Vec<Pos> = Vec::new();
+    let mut perspective = game.settings.perspective();
 
-pub fn game_loop(board: &mut Board) {
     loop {
-        board.print();
-        println!();
-        println!("Current move is: {}", board.current_move());
+        // The lock is released after drawing, so that analysis threads can print
+        let drawn = renderer.render(
+            &game.board,
+            &highlights,
+            perspective,
+            &mut io::stdout().lock(),
+        );
+        if let Err(e) = drawn {
+            eprintln!("Drawing the board failed, reason: {e}");
+            break;
+        }
+        highlights.clear();
 
-        match input::query_input() {
-            Ok(p) => {
-                if let Err(e) = board.move_piece(p.0, p.1) {
-                    println!("Movement failed, reason: {e}");
-                }
+        let status = game.board.status();
+        if status.is_over() {
+            println!("Game over: {status} ({}).", status.result());
+            break;
+        }
+
+        let side = game.board.current_move();
+        if game.settings.time.is_some() {
+            println!(
+                "White: {}, Black: {}",
+                game.clock.show(Side::White),
+                game.clock.show(Side::Black)
+            );
+        }
+        println!("Current move is: {side}");
+
+        let result = match game.settings.player(side) {
+            Player::Engine => {
+                engine_move(game);
+                Ok(())
             }
-            Err(e) => {
-                println!("Parsing position failed, reason: {e}");
-                if e.is::<QuitGame>() {
+            Player::Remote => {
+                let connection = remote.as_deref_mut();
+                if !connection.is_some_and(|c| remote_move(game, c)) {
                     break;
-                } else {
                 }
+                Ok(())
+            }
+            Player::Human => match input::query_command() {
+                Ok(Command::Moves(p)) => {
+                    highlights = game.board.legal_moves_from(p);
+                    if highlights.is_empty() {
+                        println!("No possible moves from {p}.");
+                    }
+                    Ok(())
+                }
+                Ok(Command::Flip) => {
+                    perspective = perspective.opposite();
+                    Ok(())
+                }
+                Ok(Command::Coach) => {
+                    coach = !coach;
+                    println!("Coach is {}.", if coach { "on" } else { "off" });
+                    Ok(())
+                }
+                Ok(Command::Analyze(lines)) => analyze(game, lines),
+                Ok(Command::Book) => {
+                    show_book(game);
+                    Ok(())
+                }
+                Ok(Command::Save(path)) => {
+                    match game.save(&path) {
+                        Ok(()) => println!("Game saved to {}.", path.display()),
+                        Err(e) => println!("Saving the game failed, reason: {e}"),
+                    }
+                    Ok(())
+                }
+                Ok(Command::Load(_)) if remote.is_some() => {
+                    println!("Loading a game isn't possible when playing over the network.");
+                    Ok(())
+                }
+                Ok(Command::Load(path)) => {
+                    match Game::load(&path) {
+                        Ok(mut loaded) => {
+                            loaded.book = game.book.take();
+                            *game = loaded;
+                            perspective = game.settings.perspective();
+                            println!("Game loaded from {}.", path.display());
+                        }
+                        Err(e) => println!("Loading the game failed, reason: {e}"),
+                    }
+                    Ok(())
+                }
+                Ok(Command::Drop(t, p)) => {
+                    human_move(game, Move::drop(t, p), coach, remote.as_deref_mut())
+                }
+                Ok(Command::Select(p)) => {
+                    input::query_move().and_then(|(end, promotion)| {
+                        let mut movement = Move::new(p, end);
+                        // A pawn reaching the last row is promoted to a queen by default
+                        movement.promotion =
+                            promotion.or(game.board.is_promotion(movement).then_some(Type::Queen));
+                        human_move(game, movement, coach, remote.as_deref_mut())
+                    })
+                }
+                Err(e) => Err(e),
+            },
+        };
+
+        if let Err(e) = result {
+            println!("Parsing input failed, reason: {e}");
+            if e.is::<QuitGame>() {
+                break;
             }
         }
+        if game.clock.flag_fell(side) {
+            println!(
+                "Game over: {side} ran out of time, {} wins.",
+                side.opposite()
+            );
+            break;
+        }
         println!();
     }
+
+    if let Some(connection) = remote {
+        // The other player may have left already
+        let _ = connection.send(&Message::Quit);
+    }
+}
+
+/// Performs `movement` entered by a human player and sends it to `remote`. With `coach`
+/// the player is warned about blunders first and asked whether to play it anyway.
+fn human_move(
+    game: &mut Game,
+    movement: Move,
+    coach: bool,
+    remote: Option<&mut Connection>,
+) -> Result<(), Box<dyn Error>> {
+    if coach && game.board.legal_moves().contains(&movement) {
+        let warnings = coach::review(&game.board, movement);
+        for warning in &warnings {
+            println!("Coach: this movement {warning}.");
+        }
+        if !warnings.is_empty() && !input::confirm("Play it anyway?")? {
+            return Ok(());
+        }
+    }
+    match game.board.make_move(movement) {
+        Ok(()) => {
+            game.clock.press();
+            // A lost connection shows when waiting for the opponent's movement
+            if let Some(Err(e)) = remote.map(|c| c.send(&Message::Move(movement))) {
+                println!("Sending the movement failed, reason: {e}");
+            }
+        }
+        Err(e) if movement.drop.is_some() => println!("Drop failed, reason: {e}"),
+        Err(e) => println!("Movement failed, reason: {e}"),
+    }
+    Ok(())
+}
+
+/// Waits for the movement of the player at the other end of `connection` and performs it
+/// if it is legal. Returns false if the other player has left or the game can't go on.
+fn remote_move(game: &mut Game, connection: &mut Connection) -> bool {
+    println!("Waiting for the opponent...");
+    match connection.receive() {
+        Ok(Message::Move(m)) => {
+            let san = game.board.san(m);
+            match game.board.make_move(m) {
+                Ok(()) => {
+                    game.clock.press();
+                    println!("Opponent played: {}", san.unwrap_or(m.to_string()));
+                    true
+                }
+                Err(e) => {
+                    println!("Opponent sent the illegal movement {m}, reason: {e}");
+                    false
+                }
+            }
+        }
+        Ok(Message::Quit) => {
+            println!("Opponent left the game.");
+            false
+        }
+        Ok(message) => {
+            println!("Opponent sent the unexpected message '{message}'.");
+            false
+        }
+        Err(e) => {
+            println!("Receiving the opponent's movement failed, reason: {e}");
+            false
+        }
+    }
+}
+
+/// Analyzes the position of `game`, showing the best `lines` lines, until the player
+/// presses Enter.
+fn analyze(game: &Game, lines: usize) -> Result<(), Box<dyn Error>> {
+    println!("Analyzing, press Enter to stop.");
+    let stop = AtomicBool::new(false);
+    thread::scope(|s| {
+        s.spawn(|| {
+            analysis::analyze(&game.board, &Limits::default(), lines, &stop);
+            if !stop.load(Ordering::Relaxed) {
+                println!("Analysis finished, press Enter to continue.");
+            }
+        });
+        let entered = input::wait_for_enter();
+        stop.store(true, Ordering::Relaxed);
+        entered
+    })
+}
+
+/// Lists the movements of the opening book for the position of `game` with their
+/// weights.
+fn show_book(game: &Game) {
+    let Some(book) = &game.book else {
+        println!("No opening book loaded, start the game with '--book FILE'.");
+        return;
+    };
+    let entries = book.entries(&game.board);
+    if entries.is_empty() {
+        println!("The book has no moves for this position.");
+        return;
+    }
+    let total: u32 = entries.iter().map(|e| u32::from(e.weight)).sum();
+    println!("Book moves:");
+    for e in entries {
+        let san = game.board.san(e.movement).unwrap_or_default();
+        let share = f64::from(e.weight) * 100.0 / f64::from(total.max(1));
+        println!("  {san:<8} weight {:>5} ({share:.1}%)", e.weight);
+    }
+}
+
+/// Lets the engine perform its movement for the side to move, taken from the opening
+/// book if it has one for the position, else searched.
+fn engine_move(game: &mut Game) {
+    let board = &mut game.board;
+    let from_book = game.book.as_ref().and_then(|book| book.pick(board));
+    let best = from_book.or_else(|| {
+        println!("Engine is thinking...");
+        let limits = game
+            .settings
+            .engine_limits(&game.clock, board.current_move());
+        search::search(board, &limits, &AtomicBool::new(false), |_| {})
+            .and_then(|info| info.best_move())
+    });
+
+    // The game is ongoing, so there is always a legal movement
+    if let Some(m) = best {
+        let san = board.san(m).unwrap_or_else(|_| m.to_string());
+        board.make_move_unchecked(m);
+        game.clock.press();
+        let source = if from_book.is_some() { " (book)" } else { "" };
+        println!("Engine played: {san}{source}");
+    }
 }
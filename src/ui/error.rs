@@ -28,3 +28,74 @@ impl fmt::Display for QuitGame {
 }
 
 impl Error for QuitGame {}
+
+#[derive(Debug)]
+pub struct UnknownCommand(pub String);
+
+impl fmt::Display for UnknownCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown command '{}'", self.0)
+    }
+}
+
+impl Error for UnknownCommand {}
+
+#[derive(Debug)]
+pub struct UnknownGlyphSet(pub String);
+
+impl fmt::Display for UnknownGlyphSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown glyph set '{}', expected 'solid', 'outline' or 'ascii'", self.0)
+    }
+}
+
+impl Error for UnknownGlyphSet {}
+
+#[derive(Debug)]
+pub struct UnknownPlayer(pub String);
+
+impl fmt::Display for UnknownPlayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown player '{}', expected 'human' or 'engine'", self.0)
+    }
+}
+
+impl Error for UnknownPlayer {}
+
+#[derive(Debug)]
+pub struct InvalidTimeControl(pub String);
+
+impl fmt::Display for InvalidTimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid time control '{}', expected MINUTES+INCREMENT like '5+3'", self.0)
+    }
+}
+
+impl Error for InvalidTimeControl {}
+
+/// Wrong command-line arguments, reported together with the help of the subcommand
+#[derive(Debug)]
+pub struct UsageError {
+    pub reason: String,
+    /// Subcommand whose help should be shown, `None` for the general help
+    pub subcommand: Option<&'static str>,
+}
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl Error for UsageError {}
+
+#[derive(Debug)]
+pub struct InvalidSave(pub String);
+
+impl fmt::Display for InvalidSave {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid saved game: {}", self.0)
+    }
+}
+
+impl Error for InvalidSave {}
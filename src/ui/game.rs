@@ -0,0 +1,393 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::core::board::Board;
+use crate::core::pgn::{self, PgnGame};
+use crate::core::piece::Side;
+use crate::engine::book::Book;
+use crate::engine::search::Limits;
+
+use super::error::{InvalidSave, InvalidTimeControl, UnknownPlayer};
+
+/// Time the engine thinks about a move when the game is played without clocks
+const ENGINE_MOVE_TIME: Duration = Duration::from_secs(2);
+
+/// Who makes the movements of one side
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Player {
+    #[default]
+    Human,
+    Engine,
+    /// A human playing on another machine, see `net`
+    Remote,
+}
+
+impl FromStr for Player {
+    type Err = UnknownPlayer;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Player::Human),
+            "engine" => Ok(Player::Engine),
+            _ => Err(UnknownPlayer(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Player::Human => write!(f, "human"),
+            Player::Engine => write!(f, "engine"),
+            Player::Remote => write!(f, "remote"),
+        }
+    }
+}
+
+/// Time each side has for the whole game and the increment added after every movement
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TimeControl {
+    pub initial: Duration,
+    pub increment: Duration,
+}
+
+impl TimeControl {
+    /// Longest initial time and increment accepted, so that the clocks can't overflow
+    pub const MAX: Duration = Duration::from_secs(24 * 60 * 60);
+}
+
+/// Parses a time control written as `MINUTES+INCREMENT_SECONDS`, like `5+3`,
+/// or only as `MINUTES`. Both times may be a day at most.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use rust_chess::ui::game::TimeControl;
+///
+/// let tc: TimeControl = "5+3".parse().unwrap();
+///
+/// assert_eq!(tc.initial, Duration::from_secs(300));
+/// assert_eq!(tc.increment, Duration::from_secs(3));
+/// assert!("5+".parse::<TimeControl>().is_err());
+/// assert!("1e300+0".parse::<TimeControl>().is_err());
+/// assert!("5+18446744073709551615".parse::<TimeControl>().is_err());
+/// ```
+impl FromStr for TimeControl {
+    type Err = InvalidTimeControl;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || InvalidTimeControl(s.to_string());
+        let (minutes, increment) = s.split_once('+').unwrap_or((s, "0"));
+        let minutes: f64 = minutes.parse().map_err(|_| error())?;
+        let increment: u64 = increment.parse().map_err(|_| error())?;
+        if minutes <= 0.0 {
+            return Err(error());
+        }
+        let tc = TimeControl {
+            initial: Duration::try_from_secs_f64(minutes * 60.0).map_err(|_| error())?,
+            increment: Duration::from_secs(increment),
+        };
+        if tc.initial > TimeControl::MAX || tc.increment > TimeControl::MAX {
+            return Err(error());
+        }
+        Ok(tc)
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}+{}",
+            self.initial.as_secs_f64() / 60.0,
+            self.increment.as_secs()
+        )
+    }
+}
+
+/// Settings of a game which aren't part of the position
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct Settings {
+    pub white: Player,
+    pub black: Player,
+    /// The game is played without clocks if there is no time control
+    pub time: Option<TimeControl>,
+}
+
+impl Settings {
+    /// Returns who plays `side`
+    pub fn player(&self, side: Side) -> Player {
+        match side {
+            Side::White => self.white,
+            Side::Black => self.black,
+        }
+    }
+
+    /// Returns the side the board should be shown from: Black's if only Black is played
+    /// by a human at this machine, else White's.
+    pub fn perspective(&self) -> Side {
+        if self.white != Player::Human && self.black == Player::Human {
+            Side::Black
+        } else {
+            Side::White
+        }
+    }
+
+    /// Returns how long the engine may think about its movement as `side`.
+    pub fn engine_limits(&self, clock: &Clock, side: Side) -> Limits {
+        match (self.time, clock.remaining(side)) {
+            (Some(tc), Some(remaining)) => Limits::from_clock(remaining, tc.increment, None),
+            _ => Limits::time(ENGINE_MOVE_TIME),
+        }
+    }
+}
+
+/// Chess clock measuring the time used by both sides.
+/// The clock of the side to move runs until it is pressed with `press`.
+#[derive(Debug, Clone)]
+pub struct Clock {
+    time: Option<TimeControl>,
+    /// Time used by White and by Black respectively, without the current turn
+    used: [Duration; 2],
+    /// Number of movements made by White and by Black respectively, each adding the increment
+    moves: [u32; 2],
+    /// Side which has run out of time before pressing the clock
+    flagged: Option<Side>,
+    running: Side,
+    turn_start: Instant,
+}
+
+impl Clock {
+    /// Constructs a clock with the time control `time`, running for `side`.
+    pub fn new(time: Option<TimeControl>, side: Side) -> Self {
+        Self::resume(time, side, [Duration::ZERO; 2], [0; 2])
+    }
+
+    /// Constructs a clock running for `side`, after White and Black have already `used`
+    /// some time for the given number of `moves` respectively.
+    pub fn resume(
+        time: Option<TimeControl>,
+        side: Side,
+        used: [Duration; 2],
+        moves: [u32; 2],
+    ) -> Self {
+        Self {
+            time,
+            used,
+            moves,
+            flagged: None,
+            running: side,
+            turn_start: Instant::now(),
+        }
+    }
+
+    /// Returns the time used by `side`, including the current turn
+    pub fn used(&self, side: Side) -> Duration {
+        let mut used = self.used[side as usize];
+        if side == self.running {
+            used += self.turn_start.elapsed();
+        }
+        used
+    }
+
+    /// Returns the time left to `side`, or `None` if the game is played without time control
+    pub fn remaining(&self, side: Side) -> Option<Duration> {
+        self.time.map(|tc| {
+            let increments = tc.increment.saturating_mul(self.moves[side as usize]);
+            tc.initial
+                .saturating_add(increments)
+                .saturating_sub(self.used(side))
+        })
+    }
+
+    /// Returns true if `side` has run out of time
+    pub fn flag_fell(&self, side: Side) -> bool {
+        self.flagged == Some(side) || self.remaining(side) == Some(Duration::ZERO)
+    }
+
+    /// Ends the turn of the running side, adding the increment, and starts the clock of the
+    /// other side. A side which has run out of time before stays out of time.
+    pub fn press(&mut self) {
+        let side = self.running;
+        if self.flag_fell(side) {
+            self.flagged = Some(side);
+        }
+        self.used[side as usize] += self.turn_start.elapsed();
+        self.moves[side as usize] += 1;
+        self.running = side.opposite();
+        self.turn_start = Instant::now();
+    }
+
+    /// Returns the clock of `side` as `MM:SS`: the remaining time, or the used time
+    /// without time control.
+    pub fn show(&self, side: Side) -> String {
+        let secs = self
+            .remaining(side)
+            .unwrap_or_else(|| self.used(side))
+            .as_secs();
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// A game in progress: the board with all performed movements, who plays and the clocks
+pub struct Game {
+    pub board: Board,
+    pub settings: Settings,
+    pub clock: Clock,
+    /// Opening book the engine plays from while it has movements for the position
+    pub book: Option<Book>,
+}
+
+impl Game {
+    /// Starts a game on `board`, with the clock running for the side to move.
+    pub fn new(board: Board, settings: Settings) -> Self {
+        let clock = Clock::new(settings.time, board.current_move());
+        Self {
+            board,
+            settings,
+            clock,
+            book: None,
+        }
+    }
+
+    /// Returns the game in PGN. Besides the starting position and the movements, the players
+    /// are stored in the `WhiteType` and `BlackType` tags (`human` or `program`), the time
+    /// control in the `TimeControl` tag (seconds and increment like `300+3` or `0.6+0`,
+    /// or `-`) and the
+    /// time used by each side in the custom `WhiteTimeUsed` and `BlackTimeUsed` tags
+    /// (seconds with millisecond precision like `61.250`).
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = PgnGame::from_board(&self.board);
+        for side in [Side::White, Side::Black] {
+            let player = self.settings.player(side);
+            let name = match player {
+                Player::Human => "Human",
+                Player::Engine => "rust-chess",
+                Player::Remote => "Opponent",
+            };
+            let kind = match player {
+                Player::Human | Player::Remote => "human",
+                Player::Engine => "program",
+            };
+            pgn.set_tag(&side.to_string(), name);
+            pgn.set_tag(&format!("{side}Type"), kind);
+        }
+        let time = self.settings.time.map_or("-".to_string(), |tc| {
+            format!(
+                "{}+{}",
+                tc.initial.as_secs_f64(),
+                tc.increment.as_secs_f64()
+            )
+        });
+        pgn.set_tag("TimeControl", &time);
+        for side in [Side::White, Side::Black] {
+            let used = self.clock.used(side).as_secs_f64();
+            pgn.set_tag(&format!("{side}TimeUsed"), &format!("{used:.3}"));
+        }
+        pgn.to_string()
+    }
+
+    /// Reads a game written by `to_pgn`. Missing tags fall back to human players
+    /// without time control.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::board::Board;
+    /// use rust_chess::ui::game::{Game, Settings};
+    ///
+    /// let settings = Settings {
+    ///     time: Some("0.01+2".parse().unwrap()),
+    ///     ..Settings::default()
+    /// };
+    /// let game = Game::from_pgn(&Game::new(Board::new(), settings).to_pgn()).unwrap();
+    /// assert_eq!(game.settings, settings);
+    ///
+    /// let save = "[TimeControl \"18446744073709551615+1\"]\n\n*\n";
+    /// assert!(Game::from_pgn(save).is_err());
+    /// ```
+    pub fn from_pgn(text: &str) -> Result<Game, Box<dyn Error>> {
+        let pgn = pgn::parse(text)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| InvalidSave("no game found".to_string()))?;
+        let board = pgn.replay()?;
+
+        let mut settings = Settings::default();
+        for side in [Side::White, Side::Black] {
+            let player = match pgn.tag(&format!("{side}Type")) {
+                None | Some("human") => Player::Human,
+                Some("program") => Player::Engine,
+                Some(kind) => {
+                    return Err(Box::new(InvalidSave(format!(
+                        "unknown player type '{kind}'"
+                    ))))
+                }
+            };
+            match side {
+                Side::White => settings.white = player,
+                Side::Black => settings.black = player,
+            }
+        }
+        settings.time = match pgn.tag("TimeControl") {
+            None | Some("-") => None,
+            Some(tc) => {
+                let error = || InvalidSave(format!("invalid time control '{tc}'"));
+                let seconds = |s: &str| {
+                    s.parse()
+                        .ok()
+                        .and_then(|s| Duration::try_from_secs_f64(s).ok())
+                        .filter(|&time| time <= TimeControl::MAX)
+                        .ok_or_else(error)
+                };
+                let (initial, increment) = tc.split_once('+').unwrap_or((tc, "0"));
+                Some(TimeControl {
+                    initial: seconds(initial)?,
+                    increment: seconds(increment)?,
+                })
+            }
+        };
+
+        let mut used = [Duration::ZERO; 2];
+        for side in [Side::White, Side::Black] {
+            if let Some(secs) = pgn.tag(&format!("{side}TimeUsed")) {
+                used[side as usize] = secs
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(|s| Duration::try_from_secs_f64(s).ok())
+                    .ok_or_else(|| InvalidSave(format!("invalid time used '{secs}'")))?;
+            }
+        }
+        // Every movement of the history has added the increment
+        let mut moves = [0; 2];
+        let mut side = board.start_position().current_move();
+        for _ in board.moves_played() {
+            moves[side as usize] += 1;
+            side = side.opposite();
+        }
+
+        let clock = Clock::resume(settings.time, board.current_move(), used, moves);
+        Ok(Game {
+            board,
+            settings,
+            clock,
+            book: None,
+        })
+    }
+
+    /// Writes the game to the file at `path` as described in `to_pgn`.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_pgn())
+    }
+
+    /// Reads a game saved with `save` from the file at `path`.
+    pub fn load(path: &Path) -> Result<Game, Box<dyn Error>> {
+        Game::from_pgn(&fs::read_to_string(path)?)
+    }
+}
@@ -1,54 +1,157 @@
-use std::{char, error::Error};
+use std::error::Error;
 use std::io;
-use std::io::Read;
+use std::path::PathBuf;
 
+use crate::core::piece::Type;
 use crate::core::position::Pos;
 
-use super::error::{PosParseError, QuitGame};
+use super::error::{PosParseError, QuitGame, UnknownCommand};
 
-pub struct InputPair(pub Pos, pub Pos);
+/// A command entered at the piece selection prompt
+pub enum Command {
+    /// Select the piece at the position to move it
+    Select(Pos),
+    /// Drop a piece of the type from the hand onto the position
+    Drop(Type, Pos),
+    /// Show the possible moves of the piece at the position
+    Moves(Pos),
+    /// Turn the board around
+    Flip,
+    /// Save the game to the file
+    Save(PathBuf),
+    /// Replace the game with the one saved in the file
+    Load(PathBuf),
+    /// Turn the warnings of the coach on or off
+    Coach,
+    /// Show the given number of best lines found by the engine
+    Analyze(usize),
+    /// Show the movements of the opening book for the position
+    Book,
+}
 
-pub fn query_input() -> Result<InputPair, Box<dyn Error>> {
-    println!("Select piece (example: d2), 'qq' - quits:");
-    let p1 = {
-        let mut buf = [0; 2];
-        let mut input = io::BufReader::new(io::stdin());
-        input.read_exact(&mut buf)?;
-        parse_pos(&buf)?
-    };
+/// Asks the player to select a piece or enter a command.
+/// Returns `Err(QuitGame)` when the player wants to quit.
+pub fn query_command() -> Result<Command, Box<dyn Error>> {
+    println!(
+        "Select piece (example: d2), 'N@f3' - drops a piece from the hand, 'moves d2' - shows possible moves, 'flip' - turns the board, 'coach' - turns blunder warnings on/off, 'analyze [LINES]' - shows the best lines, 'book' - shows the book moves, 'save FILE'/'load FILE' - saves/loads the game, 'qq' - quits:"
+    );
+    let line = read_line()?;
+    let line = line.trim();
 
-    println!("Select move (example: d4), 'qq' - quits:");
-    let p2 = {
-        let mut buf = [0; 2];
-        let mut input = io::BufReader::new(io::stdin());
-        input.read_exact(&mut buf)?;
-        parse_pos(&buf)?
-    };
+    // The file name is the rest of the line, so that it may contain spaces
+    if let Some(file) = line.strip_prefix("save ") {
+        return Ok(Command::Save(file.trim().into()));
+    }
+    if let Some(file) = line.strip_prefix("load ") {
+        return Ok(Command::Load(file.trim().into()));
+    }
+
+    let mut words = line.split_whitespace();
 
-    Ok(InputPair(p1, p2))
+    match (words.next(), words.next(), words.next()) {
+        (Some("moves"), Some(pos), None) => Ok(Command::Moves(parse_pos(pos)?)),
+        (Some("moves" | "save" | "load"), None, None) => {
+            Err(Box::new(PosParseError::InsufficientArgs))
+        }
+        (Some("flip"), None, None) => Ok(Command::Flip),
+        (Some("coach"), None, None) => Ok(Command::Coach),
+        (Some("book"), None, None) => Ok(Command::Book),
+        (Some("analyze"), None, None) => Ok(Command::Analyze(1)),
+        (Some("analyze"), Some(lines), None) => match lines.parse() {
+            Ok(lines) if lines > 0 => Ok(Command::Analyze(lines)),
+            _ => Err(Box::new(UnknownCommand(line.to_string()))),
+        },
+        (Some(pos), None, None) if pos.len() == 2 => Ok(Command::Select(parse_pos(pos)?)),
+        (Some(word), None, None) if word.contains('@') => {
+            let (piece, pos) = word.split_once('@').expect("checked above");
+            let mut letters = piece.chars();
+            let t = match (letters.next(), letters.next()) {
+                (None, _) => Type::Pawn(false),
+                (Some(c), None) => match Type::from_letter(c) {
+                    Some(Type::King) | None => {
+                        return Err(Box::new(UnknownCommand(line.to_string())))
+                    }
+                    Some(t) => t,
+                },
+                _ => return Err(Box::new(UnknownCommand(line.to_string()))),
+            };
+            Ok(Command::Drop(t, parse_pos(pos)?))
+        }
+        (None, _, _) => Err(Box::new(PosParseError::InsufficientArgs)),
+        _ => Err(Box::new(UnknownCommand(line.to_string()))),
+    }
 }
 
-fn parse_pos(buf: &[u8; 2]) -> Result<Pos, Box<dyn Error>> {
-    let mut i = buf.iter();
+/// Asks the player where the selected piece should move, and optionally the piece a pawn
+/// is promoted to, like `e8n` or `e8=N`. Returns `Err(QuitGame)` when the player wants to quit.
+pub fn query_move() -> Result<(Pos, Option<Type>), Box<dyn Error>> {
+    println!("Select move (example: d4, e8n to promote to a knight), 'qq' - quits:");
+    let line = read_line()?;
+    let line = line.trim();
+    if line.len() <= 2 {
+        return Ok((parse_pos(line)?, None));
+    }
+    if !line.is_char_boundary(2) {
+        return Err(Box::new(PosParseError::InvalidPos));
+    }
 
-    let column: char;
-    match i.next() {
-        Some(c) => column = *c as char,
-        None => return Err(Box::new(PosParseError::InsufficientArgs)),
+    let (pos, promotion) = line.split_at(2);
+    let promotion = promotion.strip_prefix('=').unwrap_or(promotion);
+    let mut letters = promotion.chars();
+    match (letters.next().and_then(Type::from_letter), letters.next()) {
+        (Some(t), None) => Ok((parse_pos(pos)?, Some(t))),
+        _ => Err(Box::new(PosParseError::InvalidPos)),
     }
+}
 
-    let row: u8;
-    match i.next() {
-        Some(r) => row = *r - b'0',
-        None => return Err(Box::new(PosParseError::InsufficientArgs)),
+/// Waits until the player presses Enter.
+/// Returns `Err(QuitGame)` at the end of input.
+pub fn wait_for_enter() -> Result<(), Box<dyn Error>> {
+    read_line().map(|_| ())
+}
+
+/// Asks the player `question` until it is answered with yes or no.
+/// Returns `Err(QuitGame)` when the player wants to quit.
+pub fn confirm(question: &str) -> Result<bool, Box<dyn Error>> {
+    loop {
+        println!("{question} (y/n), 'qq' - quits:");
+        match read_line()?.trim() {
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            "qq" => return Err(Box::new(QuitGame)),
+            _ => {}
+        }
     }
+}
+
+/// Reads a single line from stdin. The end of input is treated as quitting the game.
+fn read_line() -> Result<String, Box<dyn Error>> {
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line)? == 0 {
+        return Err(Box::new(QuitGame));
+    }
+    Ok(line)
+}
+
+fn parse_pos(s: &str) -> Result<Pos, Box<dyn Error>> {
+    let mut i = s.bytes();
+
+    let column: char = match i.next() {
+        Some(c) => c as char,
+        None => return Err(Box::new(PosParseError::InsufficientArgs)),
+    };
+
+    let row: u8 = match i.next() {
+        Some(r) => r - b'0',
+        None => return Err(Box::new(PosParseError::InsufficientArgs)),
+    };
 
     if column == 'q' && row == b'q' - b'0' {
         return Err(Box::new(QuitGame));
     }
 
     let pos = Pos::new(column, row);
-    if !pos.is_valid() {
+    if i.next().is_some() || !pos.is_valid() {
         Err(Box::new(PosParseError::InvalidPos))
     } else {
         Ok(pos)
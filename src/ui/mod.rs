@@ -1,3 +1,10 @@
+pub mod analysis;
 pub mod display;
 pub mod input;
 pub mod error;
+pub mod render;
+pub mod game;
+pub mod cli;
+pub mod coach;
+#[cfg(feature = "tui")]
+pub mod tui;
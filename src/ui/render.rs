@@ -0,0 +1,336 @@
+use std::env;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::core::board::{Board, IN_HAND};
+use crate::core::piece::{Piece, Side, Type};
+use crate::core::position::Pos;
+
+use super::error::UnknownGlyphSet;
+
+/// Draws a `Board` to any `io::Write`.
+///
+/// # Example
+///
+/// ```
+/// use rust_chess::core::board::Board;
+/// use rust_chess::core::piece::Side;
+/// use rust_chess::ui::render::{GlyphSet, NoColorRenderer, Renderer};
+///
+/// let renderer = NoColorRenderer { glyphs: GlyphSet::Ascii };
+/// let mut out = Vec::new();
+/// renderer.render(&Board::new(), &[], Side::White, &mut out).unwrap();
+///
+/// let text = String::from_utf8(out).unwrap();
+/// assert!(text.contains("1 | R | N | B | Q | K | B | N | R | 1"));
+///
+/// let mut out = Vec::new();
+/// renderer.render(&Board::new(), &[], Side::Black, &mut out).unwrap();
+///
+/// let text = String::from_utf8(out).unwrap();
+/// assert!(text.contains("1 | R | N | B | K | Q | B | N | R | 1"));
+/// ```
+pub trait Renderer {
+    /// Writes the state of `board` to `out` as seen by the `perspective` side, marking every
+    /// position in `highlights` and the fields of the last move made on the board.
+    /// In variants with drops the pieces in hand of each side are shown on its side.
+    fn render(
+        &self,
+        board: &Board,
+        highlights: &[Pos],
+        perspective: Side,
+        out: &mut dyn Write,
+    ) -> io::Result<()>;
+}
+
+/// Characters used to draw the pieces.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum GlyphSet {
+    /// Solid unicode glyphs for both sides (`♚`, `♟`, ...). The sides can only be told
+    /// apart by colour.
+    UnicodeSolid,
+    /// Outlined unicode glyphs for White (`♔`, `♙`, ...) and solid ones for Black.
+    UnicodeOutline,
+    /// Letters as in FEN: uppercase for White (`K`, `P`, ...), lowercase for Black.
+    Ascii,
+}
+
+impl GlyphSet {
+    /// Returns the character representing `p` in this glyph set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_chess::core::piece::{Piece, Side, Type};
+    /// use rust_chess::ui::render::GlyphSet;
+    ///
+    /// let p = Piece::new(Type::Knight, Side::White);
+    ///
+    /// assert_eq!(GlyphSet::UnicodeSolid.glyph(&p), '♞');
+    /// assert_eq!(GlyphSet::UnicodeOutline.glyph(&p), '♘');
+    /// assert_eq!(GlyphSet::Ascii.glyph(&p), 'N');
+    /// ```
+    pub fn glyph(&self, p: &Piece) -> char {
+        match self {
+            GlyphSet::UnicodeSolid => solid_glyph(p.p_type()),
+            GlyphSet::UnicodeOutline => match p.p_side() {
+                Side::White => outline_glyph(p.p_type()),
+                Side::Black => solid_glyph(p.p_type()),
+            },
+            GlyphSet::Ascii => match p.p_side() {
+                Side::White => p.p_type().letter(),
+                Side::Black => p.p_type().letter().to_ascii_lowercase(),
+            },
+        }
+    }
+}
+
+impl Default for GlyphSet {
+    /// `UnicodeSolid` when only the `unicode` feature is enabled (the default),
+    /// `Ascii` when the `ascii` feature is enabled or neither of them is.
+    fn default() -> Self {
+        if cfg!(feature = "unicode") && !cfg!(feature = "ascii") {
+            GlyphSet::UnicodeSolid
+        } else {
+            GlyphSet::Ascii
+        }
+    }
+}
+
+impl FromStr for GlyphSet {
+    type Err = UnknownGlyphSet;
+
+    /// Parses `solid`, `outline` or `ascii`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "solid" => Ok(GlyphSet::UnicodeSolid),
+            "outline" => Ok(GlyphSet::UnicodeOutline),
+            "ascii" => Ok(GlyphSet::Ascii),
+            _ => Err(UnknownGlyphSet(s.to_string())),
+        }
+    }
+}
+
+/// Renders the board with ANSI colours.
+/// Highlighted fields get a green background, empty ones are additionally marked with `*`.
+/// The fields of the last move get a blue background.
+///
+/// # Example - board at its starting position with `GlyphSet::UnicodeSolid`
+/// ```text
+///     a   b   c   d   e   f   g   h
+///   +---+---+---+---+---+---+---+---+
+/// 8 | ♜ | ♞ | ♝ | ♛ | ♚ | ♝ | ♞ | ♜ | 8
+///   +---+---+---+---+---+---+---+---+
+/// 7 | ♟ | ♟ | ♟ | ♟ | ♟ | ♟ | ♟ | ♟ | 7
+///   +---+---+---+---+---+---+---+---+
+/// 6 |   |   |   |   |   |   |   |   | 6
+///   +---+---+---+---+---+---+---+---+
+/// 5 |   |   |   |   |   |   |   |   | 5
+///   +---+---+---+---+---+---+---+---+
+/// 4 |   |   |   |   |   |   |   |   | 4
+///   +---+---+---+---+---+---+---+---+
+/// 3 |   |   |   |   |   |   |   |   | 3
+///   +---+---+---+---+---+---+---+---+
+/// 2 | ♟ | ♟ | ♟ | ♟ | ♟ | ♟ | ♟ | ♟ | 2
+///   +---+---+---+---+---+---+---+---+
+/// 1 | ♜ | ♞ | ♝ | ♛ | ♚ | ♝ | ♞ | ♜ | 1
+///   +---+---+---+---+---+---+---+---+
+///     a   b   c   d   e   f   g   h
+/// ```
+pub struct ColorRenderer {
+    pub glyphs: GlyphSet,
+}
+
+/// Renders the board without colours. Highlighted fields are enclosed in brackets and
+/// the fields of the last move in parentheses.
+/// Combined with `GlyphSet::Ascii` the output is plain ASCII.
+///
+/// # Example - board after 1. e4 seen by Black with `GlyphSet::Ascii`
+/// ```text
+///     h   g   f   e   d   c   b   a
+///   +---+---+---+---+---+---+---+---+
+/// 1 | R | N | B | K | Q | B | N | R | 1
+///   +---+---+---+---+---+---+---+---+
+/// 2 | P | P | P |( )| P | P | P | P | 2
+///   +---+---+---+---+---+---+---+---+
+/// 3 |   |   |   |   |   |   |   |   | 3
+///   +---+---+---+---+---+---+---+---+
+/// 4 |   |   |   |(P)|   |   |   |   | 4
+/// ...
+/// 8 | r | n | b | k | q | b | n | r | 8
+///   +---+---+---+---+---+---+---+---+
+///     h   g   f   e   d   c   b   a
+/// ```
+pub struct NoColorRenderer {
+    pub glyphs: GlyphSet,
+}
+
+impl Renderer for ColorRenderer {
+    fn render(
+        &self,
+        board: &Board,
+        highlights: &[Pos],
+        perspective: Side,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        draw(board, highlights, perspective, out, |piece, mark| {
+            let background = match mark {
+                Mark::Target => "\x1b[42m",
+                Mark::LastMove => "\x1b[44m",
+                Mark::None => "",
+            };
+            match piece {
+                Some(p) => {
+                    let color = match p.p_side() {
+                        Side::White => "\x1b[1;37m",
+                        Side::Black => "\x1b[1;31m",
+                    };
+                    format!("{background} {color}{} \x1b[0m", self.glyphs.glyph(p))
+                }
+                None if mark == Mark::Target => format!("{background} * \x1b[0m"),
+                None if mark == Mark::LastMove => format!("{background}   \x1b[0m"),
+                None => "   ".to_string(),
+            }
+        })
+    }
+}
+
+impl Renderer for NoColorRenderer {
+    fn render(
+        &self,
+        board: &Board,
+        highlights: &[Pos],
+        perspective: Side,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        draw(board, highlights, perspective, out, |piece, mark| {
+            let glyph = piece.map_or(' ', |p| self.glyphs.glyph(p));
+            match mark {
+                Mark::Target => format!("[{glyph}]"),
+                Mark::LastMove => format!("({glyph})"),
+                Mark::None => format!(" {glyph} "),
+            }
+        })
+    }
+}
+
+/// Picks a renderer for the terminal.
+/// `no_color` or a non-empty `NO_COLOR` environment variable (see <https://no-color.org>)
+/// selects `NoColorRenderer`, otherwise `ColorRenderer` is used.
+/// Without an explicit `glyphs` choice `GlyphSet::default()` is used, except that
+/// `GlyphSet::UnicodeSolid` is replaced with `GlyphSet::UnicodeOutline` when there are no
+/// colours to tell the sides apart.
+pub fn choose(glyphs: Option<GlyphSet>, no_color: bool) -> Box<dyn Renderer> {
+    let no_color = no_color || env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+
+    if no_color {
+        let glyphs = match glyphs.unwrap_or_default() {
+            GlyphSet::UnicodeSolid if glyphs.is_none() => GlyphSet::UnicodeOutline,
+            g => g,
+        };
+        Box::new(NoColorRenderer { glyphs })
+    } else {
+        Box::new(ColorRenderer {
+            glyphs: glyphs.unwrap_or_default(),
+        })
+    }
+}
+
+/// How a field should be marked by a renderer.
+#[derive(PartialEq, Clone, Copy)]
+enum Mark {
+    None,
+    /// The field is in the `highlights` passed to the renderer
+    Target,
+    /// The field is the start or the end of the last move
+    LastMove,
+}
+
+/// Draws the grid shared by all renderers as seen by the `perspective` side,
+/// with the row numbers and column letters on all sides, and the pieces in hand
+/// if the variant has drops.
+/// `cell` returns the 3 visible characters of a field.
+fn draw<F>(
+    board: &Board,
+    highlights: &[Pos],
+    perspective: Side,
+    out: &mut dyn Write,
+    cell: F,
+) -> io::Result<()>
+where
+    F: Fn(Option<&Piece>, Mark) -> String,
+{
+    let (rows, columns): (Vec<u8>, Vec<char>) = match perspective {
+        Side::White => ((1..=8).rev().collect(), ('a'..='h').collect()),
+        Side::Black => ((1..=8).collect(), ('a'..='h').rev().collect()),
+    };
+    let last_move = board.last_move();
+
+    let write_columns = |out: &mut dyn Write| -> io::Result<()> {
+        write!(out, "  ")?;
+        for column in &columns {
+            write!(out, "  {} ", column)?;
+        }
+        writeln!(out)
+    };
+
+    let write_hand = |out: &mut dyn Write, side: Side| -> io::Result<()> {
+        if !board.variant().drops() {
+            return Ok(());
+        }
+        let mut pieces = String::new();
+        for t in IN_HAND.iter().rev() {
+            for _ in 0..board.in_hand(side, *t) {
+                pieces.push_str(&cell(Some(&Piece::new(*t, side)), Mark::None));
+            }
+        }
+        if pieces.is_empty() {
+            pieces.push_str(" - ");
+        }
+        writeln!(out, "  {side} in hand:{pieces}")
+    };
+
+    write_hand(out, perspective.opposite())?;
+    write_columns(out)?;
+    for row in &rows {
+        writeln!(out, "  +---+---+---+---+---+---+---+---+")?;
+        write!(out, "{} |", row)?;
+        for column in &columns {
+            let pos = Pos::new(*column, *row);
+            let mark = if highlights.contains(&pos) {
+                Mark::Target
+            } else if last_move.is_some_and(|(start, end)| pos == start || pos == end) {
+                Mark::LastMove
+            } else {
+                Mark::None
+            };
+            write!(out, "{}|", cell(board.at(pos), mark))?;
+        }
+        writeln!(out, " {}", row)?;
+    }
+    writeln!(out, "  +---+---+---+---+---+---+---+---+")?;
+    write_columns(out)?;
+    write_hand(out, perspective)
+}
+
+fn solid_glyph(t: Type) -> char {
+    match t {
+        Type::King => '♚',
+        Type::Queen => '♛',
+        Type::Rook => '♜',
+        Type::Bishop => '♝',
+        Type::Knight => '♞',
+        Type::Pawn(_) => '♟',
+    }
+}
+
+fn outline_glyph(t: Type) -> char {
+    match t {
+        Type::King => '♔',
+        Type::Queen => '♕',
+        Type::Rook => '♖',
+        Type::Bishop => '♗',
+        Type::Knight => '♘',
+        Type::Pawn(_) => '♙',
+    }
+}
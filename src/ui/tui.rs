@@ -0,0 +1,431 @@
+use std::io::{self, Write};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute};
+use crossterm::style::{SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use crate::core::board::{Board, IN_HAND};
+use crate::core::movement::Move;
+use crate::core::piece::{Piece, Side, Type};
+use crate::core::position::Pos;
+use crate::engine::book::Book;
+use crate::engine::{eval, search};
+
+use super::game::{Clock, Game, Player, Settings};
+use super::render::GlyphSet;
+
+/// Column where the side panel starts
+const PANEL_X: u16 = 40;
+
+const LIGHT_FIELD: Color = Color::AnsiValue(180);
+const DARK_FIELD: Color = Color::AnsiValue(137);
+const CURSOR: Color = Color::AnsiValue(33);
+const SELECTED: Color = Color::AnsiValue(28);
+const TARGET: Color = Color::AnsiValue(71);
+const LAST_MOVE: Color = Color::AnsiValue(143);
+
+/// Runs a full-screen `game` until the players quit.
+///
+/// The cursor is moved with the arrow keys or `hjkl`, `Enter` or `Space` selects
+/// the piece under the cursor and confirms its destination, `Esc` cancels the selection,
+/// `f` turns the board around and `q` quits. In variants with drops `P`, `N`, `B`, `R`
+/// or `Q` (with `Shift`) drops that piece onto the cursor. Movements of sides played by
+/// the engine are performed automatically, from the opening book of `game` while it has
+/// movements for the position.
+pub fn run(game: &mut Game, glyphs: GlyphSet) -> io::Result<()> {
+    let _guard = TerminalGuard::enter()?;
+    let mut tui = Tui::new(glyphs, game);
+    let result = play(&mut tui, &mut game.board, game.book.as_ref());
+    game.clock = tui.clock;
+    result
+}
+
+/// Draws the game, lets the engine move and reacts to keys until the players quit.
+fn play(tui: &mut Tui, board: &mut Board, book: Option<&Book>) -> io::Result<()> {
+    let mut out = io::stdout();
+
+    loop {
+        tui.check_game_over(board);
+        tui.draw(board, &mut out)?;
+
+        if tui.game_over.is_none() && tui.settings.player(board.current_move()) == Player::Engine {
+            tui.engine_move(board, book, &mut out)?;
+            continue;
+        }
+
+        // Wake up regularly so that the clocks keep ticking
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && !tui.handle_key(board, key) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Switches the terminal to raw mode and the alternate screen,
+/// and restores it when dropped, even if drawing fails or panics.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// State of the full-screen front-end which isn't part of the `Board`.
+struct Tui {
+    glyphs: GlyphSet,
+    settings: Settings,
+    perspective: Side,
+    cursor: Pos,
+    selected: Option<Pos>,
+    targets: Vec<Pos>,
+    /// Performed movements in standard algebraic notation
+    moves: Vec<String>,
+    /// Side to move and number of the first movement in the starting position
+    first_move: (Side, u32),
+    /// Pieces captured by White and by Black respectively
+    captured: [Vec<Piece>; 2],
+    clock: Clock,
+    message: String,
+    /// Reason why the game has ended
+    game_over: Option<String>,
+}
+
+impl Tui {
+    fn new(glyphs: GlyphSet, game: &Game) -> Self {
+        // Movements made before, when a saved game is resumed
+        let mut moves = Vec::new();
+        let mut captured = [Vec::new(), Vec::new()];
+        let mut board = game.board.start_position();
+        let first_move = (board.current_move(), board.fullmove_number());
+        for m in game.board.moves_played() {
+            let side = board.current_move();
+            moves.push(board.san(m).unwrap_or_default());
+            if board.make_move(m).is_ok() {
+                captured[side as usize].extend(board.last_captured());
+            }
+        }
+
+        Self {
+            glyphs,
+            settings: game.settings,
+            perspective: game.settings.perspective(),
+            cursor: Pos::new('e', 2),
+            selected: None,
+            targets: Vec::new(),
+            moves,
+            first_move,
+            captured,
+            clock: game.clock.clone(),
+            message: String::new(),
+            game_over: None,
+        }
+    }
+
+    /// Stops the game, and the clocks, when it has ended on the board or on time.
+    fn check_game_over(&mut self, board: &Board) {
+        if self.game_over.is_some() {
+            return;
+        }
+        let side = board.current_move();
+        let status = board.status();
+        if status.is_over() {
+            self.game_over = Some(format!("Game over: {status} ({}).", status.result()));
+        } else if self.clock.flag_fell(side) {
+            self.game_over = Some(format!(
+                "Game over: {side} ran out of time, {} wins.",
+                side.opposite()
+            ));
+        }
+    }
+
+    /// Lets the engine perform its movement for the side to move, taken from `book`
+    /// if it has one for the position, else searched.
+    fn engine_move(
+        &mut self,
+        board: &mut Board,
+        book: Option<&Book>,
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        if let Some(m) = book.and_then(|book| book.pick(board)) {
+            self.perform(board, m);
+            return Ok(());
+        }
+        self.message = "Engine is thinking...".to_string();
+        self.draw(board, out)?;
+
+        let limits = self
+            .settings
+            .engine_limits(&self.clock, board.current_move());
+        let best = search::search(board, &limits, &AtomicBool::new(false), |_| {})
+            .and_then(|info| info.best_move());
+        self.message.clear();
+        if let Some(m) = best {
+            self.perform(board, m);
+        }
+        Ok(())
+    }
+
+    /// Reacts to a pressed key. Returns false when the players want to quit.
+    fn handle_key(&mut self, board: &mut Board, key: KeyEvent) -> bool {
+        self.message.clear();
+
+        // Arrows always move "visually", so their meaning depends on the perspective
+        let forward: i8 = if self.perspective == Side::White {
+            1
+        } else {
+            -1
+        };
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Char('q') => return false,
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(0, forward),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(0, -forward),
+            KeyCode::Left | KeyCode::Char('h') => self.move_cursor(-forward, 0),
+            KeyCode::Right | KeyCode::Char('l') => self.move_cursor(forward, 0),
+            KeyCode::Char('f') => self.perspective = self.perspective.opposite(),
+            KeyCode::Esc => {
+                self.selected = None;
+                self.targets.clear();
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => self.confirm(board),
+            KeyCode::Char(c @ ('P' | 'N' | 'B' | 'R' | 'Q')) => {
+                let t = Type::from_letter(c).expect("a piece letter");
+                self.drop_at_cursor(board, t);
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn move_cursor(&mut self, columns: i8, rows: i8) {
+        let column = (self.cursor.column as i8 + columns).clamp(b'a' as i8, b'h' as i8);
+        let row = (self.cursor.row as i8 + rows).clamp(1, 8);
+        self.cursor = Pos::new(column as u8 as char, row as u8);
+    }
+
+    /// Selects the piece under the cursor, or moves the selected piece to the cursor
+    /// if it is one of its targets.
+    fn confirm(&mut self, board: &mut Board) {
+        if self.game_over.is_some() {
+            return;
+        }
+        if let Some(start) = self.selected {
+            if self.targets.contains(&self.cursor) {
+                // Pawns reaching the last row become queens
+                let end = self.cursor;
+                let m = board.legal_moves().into_iter().find(|m| {
+                    m.start == start
+                        && m.end == end
+                        && matches!(m.promotion, None | Some(Type::Queen))
+                });
+                if let Some(m) = m {
+                    self.perform(board, m);
+                }
+                self.selected = None;
+                self.targets.clear();
+                return;
+            }
+        }
+
+        match board.at(self.cursor) {
+            Some(p) if p.p_side() == board.current_move() => {
+                self.targets = board.legal_moves_from(self.cursor);
+                self.selected = Some(self.cursor);
+                if self.targets.is_empty() {
+                    self.message = format!("No possible moves from {}.", self.cursor);
+                }
+            }
+            Some(_) => self.message = "Wrong piece was selected.".to_string(),
+            None => {
+                self.selected = None;
+                self.targets.clear();
+            }
+        }
+    }
+
+    /// Drops a piece of type `t` from the hand of the side to move onto the cursor.
+    fn drop_at_cursor(&mut self, board: &mut Board, t: Type) {
+        if self.game_over.is_some() || !board.variant().drops() {
+            return;
+        }
+        self.selected = None;
+        self.targets.clear();
+        self.perform(board, Move::drop(t, self.cursor));
+    }
+
+    fn perform(&mut self, board: &mut Board, m: Move) {
+        let side = board.current_move();
+        let san = board.san(m);
+
+        match board.make_move(m) {
+            Ok(()) => {
+                self.moves.push(san.unwrap_or_default());
+                self.captured[side as usize].extend(board.last_captured());
+                self.clock.press();
+            }
+            Err(e) => self.message = format!("Movement failed, reason: {e}"),
+        }
+    }
+
+    fn draw(&self, board: &Board, out: &mut impl Write) -> io::Result<()> {
+        let (_, height) = terminal::size()?;
+        queue!(out, ResetColor, Clear(ClearType::All))?;
+
+        self.draw_board(board, out)?;
+        self.draw_panel(board, height, out)?;
+
+        let help = "arrows/hjkl: move  enter: select/confirm  esc: cancel  f: flip  q: quit";
+        queue!(
+            out,
+            MoveTo(0, height.saturating_sub(2)),
+            Print(self.game_over.as_ref().unwrap_or(&self.message))
+        )?;
+        queue!(out, MoveTo(0, height.saturating_sub(1)), Print(help))?;
+        out.flush()
+    }
+
+    fn draw_board(&self, board: &Board, out: &mut impl Write) -> io::Result<()> {
+        let (rows, columns): (Vec<u8>, Vec<char>) = match self.perspective {
+            Side::White => ((1..=8).rev().collect(), ('a'..='h').collect()),
+            Side::Black => ((1..=8).collect(), ('a'..='h').rev().collect()),
+        };
+        let labels: String = columns.iter().map(|c| format!(" {c} ")).collect();
+        let last_move = board.last_move();
+
+        queue!(out, MoveTo(2, 1), Print(&labels))?;
+        for (y, row) in rows.iter().enumerate() {
+            let y = y as u16 + 2;
+            queue!(out, MoveTo(0, y), Print(format!("{row} ")))?;
+            for column in &columns {
+                let pos = Pos::new(*column, *row);
+                let background = if pos == self.cursor {
+                    CURSOR
+                } else if self.selected == Some(pos) {
+                    SELECTED
+                } else if self.targets.contains(&pos) {
+                    TARGET
+                } else if last_move.is_some_and(|(start, end)| pos == start || pos == end) {
+                    LAST_MOVE
+                } else if (*column as u8 - b'a' + row).is_multiple_of(2) {
+                    LIGHT_FIELD
+                } else {
+                    DARK_FIELD
+                };
+
+                queue!(out, SetBackgroundColor(background))?;
+                match board.at(pos) {
+                    Some(p) => {
+                        let foreground = match p.p_side() {
+                            Side::White => Color::White,
+                            Side::Black => Color::Black,
+                        };
+                        queue!(
+                            out,
+                            SetForegroundColor(foreground),
+                            SetAttribute(Attribute::Bold),
+                            Print(format!(" {} ", self.glyphs.glyph(p))),
+                            SetAttribute(Attribute::Reset),
+                        )?;
+                    }
+                    None if self.targets.contains(&pos) => queue!(out, Print(" * "))?,
+                    None => queue!(out, Print("   "))?,
+                }
+                queue!(out, ResetColor)?;
+            }
+            queue!(out, Print(format!(" {row}")))?;
+        }
+        queue!(out, MoveTo(2, 10), Print(&labels))?;
+        Ok(())
+    }
+
+    fn draw_panel(&self, board: &Board, height: u16, out: &mut impl Write) -> io::Result<()> {
+        let mut lines: Vec<String> = Vec::new();
+
+        for side in [Side::White, Side::Black] {
+            let marker = if side == board.current_move() {
+                '>'
+            } else {
+                ' '
+            };
+            lines.push(format!(
+                "{marker} {side:<6} {:<7} {}",
+                format!("({})", self.settings.player(side)),
+                self.clock.show(side)
+            ));
+        }
+        lines.push(String::new());
+
+        lines.push(format!(
+            "Evaluation: {:+.2}",
+            eval::evaluate(board) as f32 / 100.0
+        ));
+        lines.push(String::new());
+
+        for (side, captured) in [Side::White, Side::Black].iter().zip(&self.captured) {
+            let glyphs: String = captured.iter().map(|p| self.glyphs.glyph(p)).collect();
+            lines.push(format!("Captured by {side}: {glyphs}"));
+        }
+        lines.push(String::new());
+
+        if board.variant().drops() {
+            for side in [Side::White, Side::Black] {
+                let glyphs: String = IN_HAND
+                    .iter()
+                    .rev()
+                    .flat_map(|&t| {
+                        let glyph = self.glyphs.glyph(&Piece::new(t, side));
+                        std::iter::repeat_n(glyph, board.in_hand(side, t).into())
+                    })
+                    .collect();
+                lines.push(format!("In hand of {side}: {glyphs}"));
+            }
+            lines.push(String::new());
+        }
+
+        lines.push("Moves:".to_string());
+        // Black's first movement goes into the second column
+        let (side, number) = self.first_move;
+        let padding = (side == Side::Black).then_some("...");
+        let moves: Vec<&str> = padding
+            .into_iter()
+            .chain(self.moves.iter().map(String::as_str))
+            .collect();
+        let numbered: Vec<String> = moves
+            .chunks(2)
+            .zip(number..)
+            .map(|(pair, n)| format!("{n:>3}. {:<8} {}", pair[0], pair.get(1).unwrap_or(&"")))
+            .collect();
+        // Keep the latest moves visible, leaving room for the message and help lines
+        let room = (height as usize).saturating_sub(lines.len() + 4);
+        lines.extend(
+            numbered
+                .iter()
+                .skip(numbered.len().saturating_sub(room))
+                .cloned(),
+        );
+
+        for (y, line) in lines.iter().enumerate() {
+            queue!(out, MoveTo(PANEL_X, y as u16 + 1), Print(line))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+//! Bindings for JavaScript, built with the `wasm` feature for `wasm32-unknown-unknown`,
+//! for example with `wasm-pack build --target web -- --features wasm`. Movements are
+//! written in UCI notation like `e2e4`, `e7e8q` or `P@e4`.
+//!
+//! ```js
+//! import init, { Game } from "./pkg/rust_chess.js";
+//!
+//! await init();
+//! const game = Game.newGame();
+//! game.makeMove("e2e4");
+//! console.log(game.toFen(), game.legalMoves().length, game.status());
+//! ```
+
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use wasm_bindgen::prelude::*;
+
+use crate::core::board::Board;
+use crate::core::movement::Move;
+use crate::core::variant::{self, Variant};
+use crate::engine::search::{self, Limits};
+
+/// A game played by the rules of chess or one of its variants
+///
+/// The bindings are plain Rust as well, so they can be tried out natively:
+///
+/// ```
+/// use rust_chess::wasm::Game;
+///
+/// let mut game = Game::new_game(None).unwrap();
+/// assert_eq!(game.legal_moves().len(), 20);
+/// assert_eq!(game.make_move("e2e4").unwrap(), "e4");
+/// assert_eq!(game.make_move("e5").unwrap(), "e5");
+/// assert_eq!(game.undo_move().as_deref(), Some("e7e5"));
+/// assert_eq!(
+///     game.to_fen(),
+///     "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+/// );
+/// assert_eq!((game.status().as_str(), game.result().as_str()), ("ongoing", "*"));
+///
+/// let mate = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", None).unwrap();
+/// assert_eq!(mate.best_move(Some(2), None).unwrap().as_deref(), Some("a1a8"));
+///
+/// let crazyhouse = Game::new_game(Some("crazyhouse".to_string())).unwrap();
+/// assert_eq!(crazyhouse.legal_moves().len(), 20);
+/// ```
+#[wasm_bindgen]
+pub struct Game {
+    board: Board,
+}
+
+#[wasm_bindgen]
+impl Game {
+    /// Starts a game from the starting position of `variant`, standard chess if it
+    /// isn't given.
+    #[wasm_bindgen(js_name = newGame)]
+    pub fn new_game(variant: Option<String>) -> Result<Game, JsError> {
+        Ok(Game {
+            board: Board::new_variant(rules(variant)?),
+        })
+    }
+
+    /// Starts a game from the position `fen`, played by the rules of `variant`.
+    #[wasm_bindgen(js_name = fromFen)]
+    pub fn from_fen(fen: &str, variant: Option<String>) -> Result<Game, JsError> {
+        let board = Board::from_fen_variant(fen, rules(variant)?).map_err(JsError::new)?;
+        Ok(Game { board })
+    }
+
+    /// Returns the legal movements of the side to move.
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> Vec<String> {
+        self.board
+            .legal_moves()
+            .iter()
+            .map(Move::to_string)
+            .collect()
+    }
+
+    /// Performs `movement`, given in UCI notation or SAN, and returns it in SAN.
+    /// Throws if it isn't legal.
+    #[wasm_bindgen(js_name = makeMove)]
+    pub fn make_move(&mut self, movement: &str) -> Result<String, JsError> {
+        let m = match movement.parse() {
+            Ok(m) => m,
+            Err(_) => self.board.parse_san(movement).map_err(JsError::new)?,
+        };
+        let san = self.board.san(m).map_err(JsError::new)?;
+        self.board.make_move(m).map_err(JsError::new)?;
+        Ok(san)
+    }
+
+    /// Takes back the last movement, returning it if there was any.
+    #[wasm_bindgen(js_name = undoMove)]
+    pub fn undo_move(&mut self) -> Option<String> {
+        self.board.unmake_move().map(|m| m.to_string())
+    }
+
+    #[wasm_bindgen(js_name = toFen)]
+    pub fn to_fen(&self) -> String {
+        self.board.to_fen()
+    }
+
+    /// Returns whether the game goes on or how it has ended: `ongoing`, `checkmate`,
+    /// `stalemate`, `fifty-move-rule`, `repetition`, `insufficient-material` or
+    /// `variant-win`.
+    pub fn status(&self) -> String {
+        self.board.status().name().to_string()
+    }
+
+    /// Returns the result as written in PGN: `1-0`, `0-1`, `1/2-1/2` or `*`.
+    pub fn result(&self) -> String {
+        self.board.status().result().to_string()
+    }
+
+    /// Searches the best movement `depth` half-moves deep or for `millis` milliseconds,
+    /// whichever ends first, at least one of them has to be given. Returns nothing if
+    /// the game is over. The search blocks, so it is best run in a Web Worker.
+    #[wasm_bindgen(js_name = bestMove)]
+    pub fn best_move(
+        &self,
+        depth: Option<u32>,
+        millis: Option<u32>,
+    ) -> Result<Option<String>, JsError> {
+        if depth.is_none() && millis.is_none() {
+            return Err(JsError::new("either a depth or a time is needed"));
+        }
+        let limits = Limits {
+            depth,
+            time: millis.map(|ms| Duration::from_millis(ms.into())),
+            ..Limits::default()
+        };
+        let info = search::search(&self.board, &limits, &AtomicBool::new(false), |_| {});
+        Ok(info.and_then(|i| i.best_move()).map(|m| m.to_string()))
+    }
+}
+
+fn rules(variant: Option<String>) -> Result<&'static dyn Variant, JsError> {
+    match variant {
+        Some(name) => variant::by_name(&name)
+            .ok_or_else(|| JsError::new(&format!("unknown variant '{name}'"))),
+        None => Ok(&variant::Standard),
+    }
+}
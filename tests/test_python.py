@@ -0,0 +1,86 @@
+# Tests of the Python module, run with `maturin develop` and `pytest tests`
+import pytest
+
+import rust_chess
+
+START = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+
+
+def test_moves():
+    m = rust_chess.Move("e7e8q")
+    assert (m.from_square, m.to_square, m.promotion, m.drop) == ("e7", "e8", "Q", None)
+    assert rust_chess.Move("P@e4").drop == "P"
+    assert str(m) == m.uci() == "e7e8q"
+    assert repr(m) == "Move('e7e8q')"
+    assert m == rust_chess.Move("e7e8q") and len({m, rust_chess.Move("e7e8q")}) == 1
+    with pytest.raises(ValueError):
+        rust_chess.Move("e9e4")
+
+
+def test_board():
+    board = rust_chess.Board()
+    assert board.fen() == str(board) == START
+    assert repr(board) == f"Board('{START}')"
+    assert board.turn == "white" and len(board.legal_moves()) == 20
+    assert board.push("e4") == "e4"
+    assert board.push(rust_chess.Move("e7e5")) == "e5"
+    assert board.push("g1f3") == "Nf3"
+    assert [m.uci() for m in board.moves] == ["e2e4", "e7e5", "g1f3"]
+    assert board.pop() == rust_chess.Move("g1f3")
+    assert board.is_legal("Nf3") and not board.is_legal("Ke3") and not board.is_legal("e9")
+    assert board.san(board.parse_san("Nc3")) == "Nc3"
+    with pytest.raises(ValueError):
+        board.push("e5")
+    assert "1. e4 e5" in board.pgn()
+
+
+def test_copy():
+    board = rust_chess.Board()
+    copy = board.copy()
+    copy.push("d4")
+    assert board.fen() == START and copy.turn == "black"
+
+
+def test_game_over():
+    board = rust_chess.Board()
+    for m in ["f3", "e5", "g4", "Qh4#"]:
+        board.push(m)
+    assert board.is_check() and board.is_game_over()
+    assert (board.status(), board.result()) == ("checkmate", "0-1")
+    assert not rust_chess.Board().is_check()
+    assert rust_chess.Board().status() == "ongoing"
+
+
+def test_variants():
+    board = rust_chess.Board(variant="crazyhouse")
+    for m in ["e4", "d5", "exd5", "Qxd5"]:
+        board.push(m)
+    assert board.is_legal("P@e6")
+    with pytest.raises(ValueError):
+        board.push("K@e4")
+    with pytest.raises(ValueError):
+        rust_chess.Board(variant="shogi")
+    with pytest.raises(ValueError):
+        rust_chess.Board("not a fen")
+
+
+def test_perft_and_evaluate():
+    board = rust_chess.Board()
+    assert board.perft(3) == 8902
+    divide = board.perft_divide(2)
+    assert len(divide) == 20 and sum(nodes for _, nodes in divide) == 400
+    assert rust_chess.Board().evaluate() == 0
+
+
+def test_read_pgn():
+    games = rust_chess.read_pgn('[White "A"]\n[Black "B"]\n\n1. e4 e5 2. Nf3 1-0\n')
+    assert len(games) == 1
+    game = games[0]
+    assert game.tag("White") == "A" and game.tag("Event") is None
+    assert ("Black", "B") in game.tags
+    assert (game.moves, game.result, game.line) == (["e4", "e5", "Nf3"], "1-0", 1)
+    assert game.start().fen() == START
+    assert game.board().turn == "black"
+    assert "1. e4 e5 2. Nf3 1-0" in str(game)
+    with pytest.raises(ValueError):
+        rust_chess.read_pgn("1. e4 e5 2. Ke3 *")[0].board()